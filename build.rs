@@ -1,4 +1,5 @@
 extern crate ease;
+extern crate hyper;
 
 use std::env;
 use std::path::Path;
@@ -9,6 +10,22 @@ use std::io::Seek;
 use std::io::SeekFrom;
 use std::cmp::Ordering;
 use ease::{Url, Request};
+use hyper::Client;
+use hyper::status::StatusCode;
+
+// Fonts embedded into the binary so pages don't depend on Google Fonts.
+const FONTS: [(&'static str, &'static str); 3] =
+    [("OpenSans.ttf",
+      "https://raw.githubusercontent.com/google/fonts/main/apache/opensans/OpenSans%5Bwdth,wght%5D.ttf"),
+     ("OpenSans-Italic.ttf",
+      "https://raw.githubusercontent.com/google/fonts/main/apache/opensans/OpenSans-Italic%5Bwdth,wght%5D.ttf"),
+     ("Inconsolata.ttf",
+      "https://raw.githubusercontent.com/google/fonts/main/ofl/inconsolata/Inconsolata%5Bwdth,wght%5D.ttf")];
+
+// Named after the file it's downloaded from, so a build directory that
+// still has an AllCards.json from before the extended data was used
+// downloads it again instead of keeping the old one.
+const ALLCARDS_FILE: &'static str = "AllCards-x.json";
 
 #[derive(Debug, PartialEq)]
 enum VersionStatus {
@@ -16,13 +33,12 @@ enum VersionStatus {
     UpToDate,
 }
 
+// Also keeps version.json up to date, since the binary includes it to tell
+// card indexes made from other card data apart.
 fn get_allcards_version_status() -> VersionStatus {
     let out_dir = env::var("OUT_DIR").unwrap();
-    let allcards_path = Path::new(&out_dir).join("AllCards.json");
-
-    if OpenOptions::new().read(true).open(&allcards_path).is_err() {
-        return VersionStatus::OutOfDate;
-    }
+    let allcards_path = Path::new(&out_dir).join(ALLCARDS_FILE);
+    let allcards_missing = OpenOptions::new().read(true).open(&allcards_path).is_err();
 
     let version_path = Path::new(&out_dir).join("version.json");
     let mut version_file =
@@ -34,18 +50,48 @@ fn get_allcards_version_status() -> VersionStatus {
     let remote_version_body = Request::new(version_url).get().unwrap().body;
 
     match local_version_body.cmp(&remote_version_body) {
-        Ordering::Equal => VersionStatus::UpToDate,
+        Ordering::Equal if !allcards_missing => VersionStatus::UpToDate,
         _ => {
             version_file.seek(SeekFrom::Start(0)).unwrap();
+            version_file.set_len(0).unwrap();
             write!(version_file, "{}", remote_version_body).unwrap();
             VersionStatus::OutOfDate
         }
     }
 }
 
+fn download_fonts() {
+    let out_dir = env::var("OUT_DIR").unwrap();
+    let client = Client::new();
+
+    for &(file_name, url) in FONTS.iter() {
+        let font_path = Path::new(&out_dir).join(file_name);
+        if OpenOptions::new().read(true).open(&font_path).is_ok() {
+            continue;
+        }
+
+        // Read as bytes, ease only hands out bodies as strings.
+        let mut response = client.get(url).send().unwrap();
+        // Otherwise an error page would be embedded as the font.
+        if response.status != StatusCode::Ok {
+            panic!("Could not download {}: {}", url, response.status);
+        }
+        let mut body = Vec::new();
+        response.read_to_end(&mut body).unwrap();
+
+        let mut font_file = OpenOptions::new()
+            .create(true)
+            .truncate(true)
+            .write(true)
+            .open(&font_path)
+            .unwrap();
+        font_file.write_all(&body).unwrap();
+    }
+}
+
 fn main() {
     let out_dir = env::var("OUT_DIR").unwrap();
-    let allcards_path = Path::new(&out_dir).join("AllCards.json");
+    let allcards_path = Path::new(&out_dir).join(ALLCARDS_FILE);
 
     match get_allcards_version_status() {
         VersionStatus::UpToDate => {}
@@ -57,11 +103,15 @@ fn main() {
                 .open(&allcards_path)
                 .unwrap();
 
-            let allcards_url = Url::parse("http://mtgjson.com/json/AllCards.json").unwrap();
+            // The extended file, for the card names in other languages.
+            let allcards_url =
+                Url::parse(&format!("http://mtgjson.com/json/{}", ALLCARDS_FILE)).unwrap();
             let allcards_body = Request::new(allcards_url).get().unwrap().body;
 
             write!(allcards_file, "{}", allcards_body).unwrap();
 
         }
     }
+
+    download_fonts();
 }
@@ -0,0 +1,133 @@
+use std::collections::BTreeMap;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::Hasher;
+use std::str;
+
+use super::rustc_serialize::base64::{ToBase64, STANDARD};
+
+/// Files compiled into the binary and served from STATIC_ROUTE.
+pub struct Asset {
+    pub name: &'static str,
+    pub content_type: &'static str,
+    pub body: &'static [u8],
+}
+
+pub const STATIC_ROUTE: &'static str = "/proxygen/static";
+
+macro_rules! css_asset {
+    ($name:expr) => {
+        Asset {
+            name: $name,
+            content_type: "text/css; charset=utf-8",
+            body: include_bytes!($name),
+        }
+    }
+}
+
+macro_rules! js_asset {
+    ($name:expr) => {
+        Asset {
+            name: $name,
+            content_type: "application/javascript; charset=utf-8",
+            body: include_bytes!($name),
+        }
+    }
+}
+
+macro_rules! font_asset {
+    ($name:expr) => {
+        Asset {
+            name: $name,
+            content_type: "font/ttf",
+            body: include_bytes!(concat!(env!("OUT_DIR"), "/", $name)),
+        }
+    }
+}
+
+pub static ASSETS: [Asset; 11] = [css_asset!("proxygen.css"),
+                                  css_asset!("results.css"),
+                                  css_asset!("frames.css"),
+                                  css_asset!("themes/ink.css"),
+                                  css_asset!("themes/large_print.css"),
+                                  css_asset!("themes/classic.css"),
+                                  js_asset!("search.js"),
+                                  js_asset!("complete.js"),
+                                  font_asset!("OpenSans.ttf"),
+                                  font_asset!("OpenSans-Italic.ttf"),
+                                  font_asset!("Inconsolata.ttf")];
+
+// (family, style, asset name). The files are variable fonts, so one file
+// covers every weight.
+const FONT_FACES: [(&'static str, &'static str, &'static str); 3] =
+    [("Open Sans", "normal", "OpenSans.ttf"),
+     ("Open Sans", "italic", "OpenSans-Italic.ttf"),
+     ("Inconsolata", "normal", "Inconsolata.ttf")];
+
+lazy_static!{
+    // Content hashes, so an asset's URL changes whenever its contents do and
+    // browsers can cache each URL forever.
+    static ref VERSIONS: BTreeMap<&'static str, String> = ASSETS.iter()
+        .map(|asset| {
+            let mut hasher = DefaultHasher::new();
+            hasher.write(asset.body);
+            (asset.name, format!("{:016x}", hasher.finish()))
+        })
+        .collect();
+}
+
+pub fn find(name: &str) -> Option<&'static Asset> {
+    ASSETS.iter().find(|asset| asset.name == name)
+}
+
+impl Asset {
+    pub fn version(&self) -> &'static str {
+        &VERSIONS[self.name]
+    }
+
+    /// Versioned path the server serves this asset from.
+    pub fn path(&self) -> String {
+        format!("{}/{}/{}", STATIC_ROUTE, self.version(), self.name)
+    }
+
+    fn text(&self) -> &'static str {
+        str::from_utf8(self.body).unwrap()
+    }
+
+    fn url(&self, inline: bool) -> String {
+        if inline {
+            format!("data:{};base64,{}", self.content_type, self.body.to_base64(STANDARD))
+        } else {
+            self.path()
+        }
+    }
+}
+
+/// Markup pulling in a stylesheet asset, either linked or inlined. Inline
+/// assets are for pages that are saved to disk rather than served.
+pub fn stylesheet(name: &str, inline: bool) -> String {
+    let asset = find(name).unwrap();
+    if inline {
+        format!("<style>{}</style>", asset.text())
+    } else {
+        format!("<link href=\"{}\" rel=\"stylesheet\">", asset.path())
+    }
+}
+
+/// Markup loading a script asset. Scripts are only used by served pages.
+pub fn script(name: &str) -> String {
+    format!("<script src=\"{}\" defer></script>", find(name).unwrap().path())
+}
+
+/// @font-face rules for the embedded fonts.
+pub fn font_css(inline: bool) -> String {
+    FONT_FACES.iter()
+        .filter_map(|&(family, style, name)| find(name).map(|asset| (family, style, asset)))
+        .map(|(family, style, asset)| {
+            format!("@font-face {{ font-family: '{}'; font-style: {}; font-weight: 300 800; \
+                     src: url(\"{}\") format(\"truetype\"); }}\n",
+                    family,
+                    style,
+                    asset.url(inline))
+        })
+        .collect()
+}
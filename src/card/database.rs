@@ -1,42 +1,101 @@
-use std::collections::BTreeMap;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use super::Card;
+use super::index::{self, Index};
+use super::mana::{Color, ManaCost};
 use super::super::error::ProxygenError;
 
+use super::super::bincode::SizeLimit;
+use super::super::bincode::serde::{deserialize, serialize};
+use super::super::regex::Regex;
+use super::super::serde::{Deserialize, Serialize};
 use super::super::serde_json;
 
-use std::iter::FromIterator;
+use super::super::unicode_normalization::UnicodeNormalization;
+use super::super::unicode_normalization::char::is_combining_mark;
 
-// http://mtgjson.com/json/AllCards.json.zip
-const ALLCARDS_JSON: &'static str = include_str!(concat!(env!("OUT_DIR"), "/AllCards.json"));
+// http://mtgjson.com/json/AllCards-x.json.zip
+const ALLCARDS_JSON: &'static str = include_str!(concat!(env!("OUT_DIR"), "/AllCards-x.json"));
 
+/// Whether accents can be dropped from `c` the way people leave them out
+/// when typing: on Latin, Greek and Cyrillic letters. Elsewhere the marks
+/// can make another letter, like the voicing marks that turn カ into ガ.
+fn drops_accents(c: char) -> bool {
+    match c {
+        // Latin
+        '\u{0}'...'\u{24f}' |
+        '\u{1e00}'...'\u{1eff}' |
+        '\u{2c60}'...'\u{2c7f}' |
+        '\u{a720}'...'\u{a7ff}' |
+        '\u{ab30}'...'\u{ab6f}' |
+        // Greek
+        '\u{370}'...'\u{3ff}' |
+        '\u{1f00}'...'\u{1fff}' |
+        // Cyrillic
+        '\u{400}'...'\u{52f}' |
+        '\u{1c80}'...'\u{1c8f}' |
+        '\u{a640}'...'\u{a69f}' => true,
+        _ => false,
+    }
+}
+
+/// The NFKD form of `name`, without the accents `drops_accents` allows
+/// dropping.
+fn strip_accents(name: &str) -> String {
+    let mut stripped = String::new();
+    let mut base = ' ';
+    for c in name.nfkd() {
+        if !is_combining_mark(c) {
+            base = c;
+            stripped.push(c);
+        } else if !drops_accents(base) {
+            stripped.push(c);
+        }
+    }
+    stripped
+}
+
+/// Folds a card name to the key it is looked up by, so names typed with
+/// other capitalization, accents or punctuation still match. "Lim-Dûl the
+/// Necromancer", "lim dul the necromancer" and "Lim–Dûl the Necromancer"
+/// all give "lim dul the necromancer".
 pub fn sanitize_name(name: &str) -> String {
-    // These should cover all non-unhinged/unglued cases.
-    // People who want unhinged/unglued stuff can make sure they're precise
-    name.to_lowercase()
-        .replace("\u{e6}", "ae")
-        .replace("\u{e0}", "a")
-        .replace("\u{e1}", "a")
-        .replace("\u{e2}", "a")
-        .replace("\u{e9}", "e")
-        .replace("\u{ed}", "i")
-        .replace("\u{f6}", "o")
-        .replace("\u{fa}", "u")
-        .replace("\u{fb}", "u")
-        .replace(",", "")
-        .replace("\u{e2}", "'") //some users type ’ instead of ', for some reason.
-        .replace("'", "")
-        .replace("-", " ")
+    let mut folded = String::new();
+    // NFKD splits accented letters into a base letter and combining marks,
+    // and turns compatibility characters like non-breaking spaces into
+    // their plain forms.
+    for c in strip_accents(name).chars() {
+        match c {
+            // Apostrophes, including the curly ones word processors insert,
+            // and commas are dropped.
+            '\'' | '\u{2018}' | '\u{2019}' | '\u{2bc}' | '\u{b4}' | '`' | ',' => {}
+            '\u{201c}' | '\u{201d}' => folded.push('"'),
+            // Hyphens and dashes separate words like spaces do.
+            '-' | '\u{2010}'...'\u{2015}' | '\u{2212}' => folded.push(' '),
+            // Not a ligature to Unicode, so NFKD leaves it alone.
+            '\u{e6}' | '\u{c6}' => folded.push_str("ae"),
+            c if c.is_whitespace() => folded.push(' '),
+            c => folded.extend(c.to_lowercase()),
+        }
+    }
+    folded.split_whitespace().collect::<Vec<_>>().join(" ")
 }
 
 // Allow non snake case for automatic deserialize
 #[allow(non_snake_case)]
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 struct DatabaseEntry {
     layout: String,
     name: String,
     sanetype: String,
     names: Option<Vec<String>>,
     manaCost: Option<String>,
+    colors: Option<Vec<String>>,
     supertypes: Option<Vec<String>>,
     types: Option<Vec<String>>,
     subtypes: Option<Vec<String>>,
@@ -44,59 +103,486 @@ struct DatabaseEntry {
     power: Option<String>,
     toughness: Option<String>,
     loyalty: Option<u64>,
+    legalities: Option<Vec<Legality>>,
+    foreignData: Option<Vec<ForeignEntry>>,
+    // Older card data only has the names.
+    foreignNames: Option<Vec<ForeignEntry>>,
+}
+
+/// Whether a card may be played in a format. `legality` is "Legal",
+/// "Banned" or "Restricted". Formats a card isn't legal in aren't listed.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Legality {
+    pub format: String,
+    pub legality: String,
+}
+
+/// A card's name, and possibly its type line and text, in another language.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct ForeignEntry {
+    language: String,
+    name: String,
+    sanetype: Option<String>,
+    text: Option<String>,
+}
+
+impl DatabaseEntry {
+    fn foreign(&self) -> Vec<&ForeignEntry> {
+        self.foreignData.iter().chain(self.foreignNames.iter()).flat_map(|v| v.iter()).collect()
+    }
+
+    /// Whether a deck may have any number of copies: basic lands, and cards
+    /// like Relentless Rats that say so.
+    fn unlimited_copies(&self) -> bool {
+        let basic = self.supertypes.iter().flat_map(|v| v.iter()).any(|t| t == "Basic");
+        let says_so = self.text
+            .as_ref()
+            .map_or(false, |text| text.contains("A deck can have any number of cards named"));
+        basic || says_so
+    }
+
+    /// The entry with its name, type line and text in the given language.
+    /// Cards are left in English unless the type line, and the text if the
+    /// card has any, are translated too, rather than mixing languages on one
+    /// card. Card data with only translated names leaves every card in
+    /// English.
+    fn localized(mut self, language: &str) -> DatabaseEntry {
+        let found = self.foreign()
+            .into_iter()
+            .find(|data| {
+                data.language == language && data.sanetype.is_some() &&
+                (data.text.is_some() || self.text.is_none())
+            })
+            .cloned();
+        if let Some(data) = found {
+            self.name = data.name;
+            self.sanetype = data.sanetype.unwrap();
+            if data.text.is_some() {
+                self.text = data.text;
+            }
+        }
+        self
+    }
+}
+
+// Tables of the card index.
+const ENTRIES: usize = 0;
+/// Card names in other languages, to the key of the English name and the
+/// language the name is in.
+const FOREIGN: usize = 1;
+
+// The version of the card data the binary was built with, from build.rs.
+const DATA_VERSION: &'static str = include_str!(concat!(env!("OUT_DIR"), "/version.json"));
+
+const LAYOUTS: [&'static str; 6] = ["normal", "split", "flip", "double-faced", "leveler", "meld"];
+
+/// Where the cards are kept: parsed from the card data built into the
+/// binary, or in a card index mapped from disk and decoded as needed.
+enum Store {
+    Parsed {
+        map: BTreeMap<String, DatabaseEntry>,
+        /// Card names in other languages, to the key of the English name and
+        /// the language the name is in.
+        foreign: BTreeMap<String, (String, String)>,
+    },
+    Indexed(Index),
 }
 
-#[derive(Debug)]
+/// An entry as it is stored, decoded only when it's needed.
+enum Stored<'a> {
+    Parsed(&'a DatabaseEntry),
+    Encoded(&'a [u8]),
+}
+
+impl<'a> Stored<'a> {
+    fn entry(&self) -> Result<Cow<'a, DatabaseEntry>, ProxygenError> {
+        match *self {
+            Stored::Parsed(entry) => Ok(Cow::Borrowed(entry)),
+            Stored::Encoded(bytes) => decode(bytes).map(Cow::Owned),
+        }
+    }
+}
+
+/// Cards keyed by their sanitized names.
 pub struct Database {
-    map: BTreeMap<String, DatabaseEntry>,
+    store: Store,
+    /// Cards already looked up, by key and the language they're drawn in,
+    /// so each is only parsed once.
+    cards: Mutex<HashMap<(String, Option<String>), Arc<Card>>>,
+}
+
+fn decode<T: Deserialize>(bytes: &[u8]) -> Result<T, ProxygenError> {
+    deserialize(bytes).map_err(|e| ProxygenError::DatabaseError(e.to_string()))
 }
 
-fn make_database() -> Database {
-    let sane_allcards_json = String::from(ALLCARDS_JSON).replace("\"type\":", "\"sanetype\":");
-    let bad_map: BTreeMap<String, DatabaseEntry> = serde_json::from_str(&sane_allcards_json)
-        .unwrap();
+fn encode<T: Serialize>(value: &T) -> Result<Vec<u8>, String> {
+    serialize(value, SizeLimit::Infinite).map_err(|e| e.to_string())
+}
 
-    let good_map: BTreeMap<String, DatabaseEntry> = BTreeMap::from_iter(bad_map.iter()
-        .map(|(key, value)| (key.clone(), value.clone()))
+fn millis(duration: Duration) -> u64 {
+    duration.as_secs() * 1000 + duration.subsec_nanos() as u64 / 1_000_000
+}
+
+/// How much memory the process has resident, as Linux reports it, for the
+/// startup log. Empty on other systems.
+fn resident_memory() -> String {
+    let mut status = String::new();
+    if File::open("/proc/self/status").and_then(|mut f| f.read_to_string(&mut status)).is_err() {
+        return String::new();
+    }
+    status.lines()
+        .find(|line| line.starts_with("VmRSS:"))
+        .map(|line| format!(", {} resident", line["VmRSS:".len()..].trim()))
+        .unwrap_or_else(String::new)
+}
+
+/// Parses the card data built into the binary into entries by key, and
+/// translated names.
+fn parse_card_data() -> Result<(BTreeMap<String, DatabaseEntry>,
+                                BTreeMap<String, (String, String)>),
+                               String> {
+    let sane_allcards_json = ALLCARDS_JSON.replace("\"type\":", "\"sanetype\":");
+    let all_cards: BTreeMap<String, DatabaseEntry> =
+        try!(serde_json::from_str(&sane_allcards_json).map_err(|e| e.to_string()));
+
+    // û -> u, example: Lim-Dûl the Necromancer
+    let map: BTreeMap<String, DatabaseEntry> = all_cards.into_iter()
         .map(|(key, value)| (sanitize_name(&key), value))
-        .filter(|&(_, ref value)| {
-            vec!["normal", "split", "flip", "double-faced", "leveler", "meld"]
-                .contains(&value.layout.as_str())
-        })); // û -> u, example: Lim-Dûl the Necromancer
+        .filter(|&(_, ref value)| LAYOUTS.contains(&value.layout.as_str()))
+        .collect();
+
+    let mut foreign = BTreeMap::new();
+    for (key, entry) in &map {
+        for data in entry.foreign() {
+            // Some translations are shared between cards. The first one keeps it.
+            foreign.entry(sanitize_name(&data.name))
+                .or_insert_with(|| (key.clone(), data.language.clone()));
+        }
+    }
+    Ok((map, foreign))
+}
 
-    Database { map: good_map }
+/// Saves the card data built into the binary as a card index at `path`,
+/// for `proxygen build-db`. Returns the size of the index in bytes.
+pub fn save_index(path: &str) -> Result<usize, ProxygenError> {
+    let (map, foreign) = try!(parse_card_data().map_err(ProxygenError::DatabaseError));
+
+    let mut entries = Vec::new();
+    for (key, entry) in map {
+        entries.push((key, try!(encode(&entry).map_err(ProxygenError::DatabaseError))));
+    }
+    let mut foreign_names = Vec::new();
+    for (name, english) in foreign {
+        foreign_names.push((name, try!(encode(&english).map_err(ProxygenError::DatabaseError))));
+    }
+    let bytes = index::build(DATA_VERSION, &[entries, foreign_names]);
+
+    try!(index::write(path, &bytes));
+    Ok(bytes.len())
 }
 
 lazy_static!{
-    pub static ref DATABASE: Database = make_database();
+    static ref INDEX_PATH: Mutex<Option<String>> = Mutex::new(None);
+    // Kept as a message rather than a ProxygenError so every lookup can
+    // report it, not just the first.
+    static ref DATABASE: Result<Database, String> = load_database();
+}
+
+/// Has the database read the card index at `path`, if there is one there,
+/// instead of parsing the card data built into the binary. Only has an
+/// effect before the database is first used.
+pub fn use_index(path: &str) {
+    *INDEX_PATH.lock().unwrap() = Some(String::from(path));
+}
+
+fn load_database() -> Result<Database, String> {
+    let start = Instant::now();
+    let mut store = None;
+    if let Some(ref path) = *INDEX_PATH.lock().unwrap() {
+        if Path::new(path).exists() {
+            match Index::open(path, DATA_VERSION) {
+                Ok(index) => {
+                    println!("Mapped card index {} in {} ms{}",
+                             path,
+                             millis(start.elapsed()),
+                             resident_memory());
+                    store = Some(Store::Indexed(index));
+                }
+                // Everything still works without it, startup is just slower.
+                Err(e) => writeln!(io::stderr(), "Not using the card index: {}", e).unwrap(),
+            }
+        }
+    }
+
+    let store = match store {
+        Some(v) => v,
+        None => {
+            let (map, foreign) = try!(parse_card_data());
+            println!("Parsed card data in {} ms{}", millis(start.elapsed()), resident_memory());
+            Store::Parsed {
+                map: map,
+                foreign: foreign,
+            }
+        }
+    };
+    Ok(Database {
+        store: store,
+        cards: Mutex::new(HashMap::new()),
+    })
+}
+
+/// The card database, built on first use.
+pub fn database() -> Result<&'static Database, ProxygenError> {
+    match *DATABASE {
+        Ok(ref db) => Ok(db),
+        Err(ref e) => Err(ProxygenError::DatabaseError(e.clone())),
+    }
+}
+
+/// The key of the card a name belongs to, in whatever language it's
+/// listed, the same as `Card::key` gives. None if it isn't a card's name.
+pub fn card_key(card_name: &str) -> Option<String> {
+    let db = match database() {
+        Ok(v) => v,
+        Err(_) => return None,
+    };
+    let key = match db.find(card_name) {
+        Ok((key, _)) => key,
+        Err(_) => return None,
+    };
+    // Any part of a card with more than one finds the card, which goes by
+    // its first part.
+    match db.entry(&key).ok().and_then(|entry| entry.names) {
+        Some(ref names) if !names.is_empty() => Some(sanitize_name(&names[0])),
+        _ => Some(key),
+    }
+}
+
+/// Filters for finding cards without knowing their exact names. Every
+/// filter that is set has to match.
+#[derive(Debug, Default)]
+pub struct Search {
+    /// Part of the name, compared the way lookups compare names.
+    pub name: Option<String>,
+    /// Part of the English type line, like "Elf" or "Legendary Creature".
+    pub typeline: Option<String>,
+    /// Pattern the English rules text has to match.
+    pub oracle: Option<Regex>,
+    pub mana_value: Option<u64>,
+    /// Colors the card has to have. It may have others too.
+    pub colors: Vec<Color>,
+}
+
+impl Search {
+    pub fn is_empty(&self) -> bool {
+        self.name.is_none() && self.typeline.is_none() && self.oracle.is_none() &&
+        self.mana_value.is_none() && self.colors.is_empty()
+    }
+
+    /// Whether an entry passes the filters other than the name, which is
+    /// checked against its key before the entry is decoded.
+    fn matches(&self, entry: &DatabaseEntry) -> bool {
+        self.typeline.as_ref().map_or(true, |typeline| {
+            entry.sanetype.to_lowercase().contains(&typeline.to_lowercase())
+        }) &&
+        self.oracle.as_ref().map_or(true, |oracle| {
+            entry.text.as_ref().map_or(false, |text| oracle.is_match(text))
+        }) &&
+        self.mana_value.map_or(true, |mana_value| {
+            ManaCost::parse(entry.manaCost.as_ref().map_or("", |c| c)).mana_value() == mana_value
+        }) &&
+        {
+            let colors = entry.colors.as_ref().map_or(&[][..], |v| &v[..]);
+            self.colors.iter().all(|color| colors.iter().any(|c| c == color.name()))
+        }
+    }
+}
+
+/// Names of the cards matching `search`, in alphabetical order, at most
+/// `limit` of them. Each part of a multi-part card is found on its own. A
+/// search without filters finds nothing, rather than the whole database.
+pub fn search(search: &Search, limit: usize) -> Result<Vec<String>, ProxygenError> {
+    let db = try!(database());
+    if search.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let name = search.name.as_ref().map(|name| sanitize_name(name));
+    let mut found = Vec::new();
+    for (key, stored) in db.entries_from("") {
+        if found.len() == limit {
+            break;
+        }
+        if name.as_ref().map_or(false, |name| !key.contains(name.as_str())) {
+            continue;
+        }
+        let entry = try!(stored.entry());
+        if search.matches(&entry) {
+            found.push(entry.name.clone());
+        }
+    }
+    Ok(found)
+}
+
+/// Names of the cards whose names start with `prefix`, compared the way
+/// lookups compare names, in alphabetical order and at most `limit` of them.
+pub fn complete(prefix: &str, limit: usize) -> Result<Vec<String>, ProxygenError> {
+    let db = try!(database());
+    let prefix = sanitize_name(prefix);
+    if prefix.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    // Keys are sorted, so the matches are the run of keys starting at the
+    // first one that doesn't sort before the prefix.
+    let mut names = Vec::new();
+    for (key, stored) in db.entries_from(&prefix) {
+        if !key.starts_with(prefix.as_str()) || names.len() == limit {
+            break;
+        }
+        names.push(try!(stored.entry()).name.clone());
+    }
+    Ok(names)
 }
 
 impl Database {
-    fn get_entry(&self, card_name: &str) -> Result<DatabaseEntry, ProxygenError> {
+    fn stored(&self, key: &str) -> Option<Stored> {
+        match self.store {
+            Store::Parsed { ref map, .. } => map.get(key).map(Stored::Parsed),
+            Store::Indexed(ref index) => index.get(ENTRIES, key).map(Stored::Encoded),
+        }
+    }
+
+    /// The key of the card a translated name belongs to, and the language
+    /// of the name.
+    fn foreign(&self, name: &str) -> Result<Option<(String, String)>, ProxygenError> {
+        match self.store {
+            Store::Parsed { ref foreign, .. } => Ok(foreign.get(name).cloned()),
+            Store::Indexed(ref index) => {
+                match index.get(FOREIGN, name) {
+                    Some(bytes) => decode(bytes).map(Some),
+                    None => Ok(None),
+                }
+            }
+        }
+    }
+
+    /// Entries in key order, starting at the first key that doesn't sort
+    /// before `from`.
+    fn entries_from<'a>(&'a self,
+                        from: &str)
+                        -> Box<Iterator<Item = (&'a str, Stored<'a>)> + 'a> {
+        match self.store {
+            Store::Parsed { ref map, .. } => {
+                Box::new(map.range(String::from(from)..)
+                    .map(|(key, entry)| (key.as_str(), Stored::Parsed(entry))))
+            }
+            Store::Indexed(ref index) => {
+                Box::new((index.lower_bound(ENTRIES, from)..index.len(ENTRIES)).map(move |i| {
+                    let (key, bytes) = index.record(ENTRIES, i);
+                    (key, Stored::Encoded(bytes))
+                }))
+            }
+        }
+    }
+
+    /// Finds a card by its English name, or failing that by its name in
+    /// another language. Gives the card's key, and the language of the
+    /// name if it wasn't English.
+    fn find(&self, card_name: &str) -> Result<(String, Option<String>), ProxygenError> {
         let sane_card_name = sanitize_name(card_name);
-        match self.map.get(&sane_card_name) {
-            Some(v) => Ok(v.clone()),
+        if self.stored(&sane_card_name).is_some() {
+            return Ok((sane_card_name, None));
+        }
+        match try!(self.foreign(&sane_card_name)) {
+            Some((key, language)) => Ok((key, Some(language))),
             None => Err(ProxygenError::InvalidCardName(String::from(card_name))),
         }
     }
 
-    pub fn get(&self, card_name: &str) -> Result<Card, ProxygenError> {
-        let entry = try!(self.get_entry(card_name));
+    fn entry(&self, key: &str) -> Result<DatabaseEntry, ProxygenError> {
+        match self.stored(key) {
+            Some(stored) => stored.entry().map(Cow::into_owned),
+            None => Err(ProxygenError::DatabaseError(format!("no card {:?}", key))),
+        }
+    }
 
-        self.parse_card(entry)
+    fn get_entry(&self, card_name: &str) -> Result<DatabaseEntry, ProxygenError> {
+        self.find(card_name).and_then(|(key, _)| self.entry(&key))
+    }
+
+    /// The card with the given key, drawn in `language`. Parsed on first
+    /// use and shared after that.
+    fn card(&self, key: String, language: Option<&str>) -> Result<Arc<Card>, ProxygenError> {
+        let cache_key = (key, language.map(String::from));
+        if let Some(card) = self.cards.lock().unwrap().get(&cache_key) {
+            return Ok(card.clone());
+        }
+
+        // Parsed without holding the lock, so other lookups aren't held up.
+        // Two threads might both parse a new card, which is harmless.
+        let entry = try!(self.entry(&cache_key.0));
+        let card = Arc::new(try!(self.parse_card(entry, language)));
+        self.cards.lock().unwrap().insert(cache_key, card.clone());
+        Ok(card)
+    }
+
+    pub fn get(&self, card_name: &str) -> Result<Arc<Card>, ProxygenError> {
+        let (key, _) = try!(self.find(card_name));
+
+        self.card(key, None)
+    }
+
+    /// Like `get`, but a card listed by a name in another language is drawn
+    /// in that language, where the card data has it.
+    pub fn get_as_listed(&self, card_name: &str) -> Result<Arc<Card>, ProxygenError> {
+        let (key, language) = try!(self.find(card_name));
+
+        self.card(key, language.as_ref().map(|l| l.as_str()))
+    }
+
+    /// Like `get`, but draws the card in `language`, as the card data names
+    /// it, where the card data has it.
+    pub fn get_in(&self, card_name: &str, language: &str) -> Result<Arc<Card>, ProxygenError> {
+        let (key, _) = try!(self.find(card_name));
+
+        self.card(key, Some(language))
     }
 
-    fn parse_card(&self, entry: DatabaseEntry) -> Result<Card, ProxygenError> {
+    fn parse_card(&self,
+                  entry: DatabaseEntry,
+                  language: Option<&str>)
+                  -> Result<Card, ProxygenError> {
+        // Decided before translating, since they go by the English card.
+        let key = sanitize_name(&entry.name);
+        let unlimited_copies = entry.unlimited_copies();
+        let entry = match language {
+            Some(language) => entry.localized(language),
+            None => entry,
+        };
         match entry.layout.as_str() {
             "normal" | "leveler" => {
+                let manacost = ManaCost::parse(entry.manaCost.as_ref().map_or("", |c| c));
+                // From the card data rather than the cost, to include color
+                // indicators and lands' colors.
+                let colors = entry.colors
+                    .iter()
+                    .flat_map(|v| v.iter())
+                    .filter_map(|c| Color::from_name(c))
+                    .collect();
                 let types = entry.types.unwrap_or_default();
                 let subtypes = entry.subtypes.unwrap_or_default();
                 if types.contains(&String::from("Creature")) ||
                    subtypes.contains(&String::from("Vehicle")) {
                     Ok(Card::Creature {
                         name: entry.name,
-                        manacost: entry.manaCost.unwrap_or_default(),
+                        key: key,
+                        manacost: manacost,
+                        colors: colors,
                         typeline: entry.sanetype,
+                        types: types,
+                        legalities: entry.legalities.unwrap_or_default(),
+                        unlimited_copies: unlimited_copies,
                         text: entry.text.unwrap_or_default(),
                         power: entry.power.unwrap_or_default(),
                         toughness: entry.toughness.unwrap_or_default(),
@@ -104,16 +590,26 @@ impl Database {
                 } else if types.contains(&String::from("Planeswalker")) {
                     Ok(Card::Planeswalker {
                         name: entry.name,
-                        manacost: entry.manaCost.unwrap_or_default(),
+                        key: key,
+                        manacost: manacost,
+                        colors: colors,
                         typeline: entry.sanetype,
+                        types: types,
+                        legalities: entry.legalities.unwrap_or_default(),
+                        unlimited_copies: unlimited_copies,
                         text: entry.text.unwrap_or_default(),
                         loyalty: entry.loyalty.unwrap_or_default(),
                     })
                 } else {
                     Ok(Card::Noncreature {
                         name: entry.name,
-                        manacost: entry.manaCost.unwrap_or_default(),
+                        key: key,
+                        manacost: manacost,
+                        colors: colors,
                         typeline: entry.sanetype,
+                        types: types,
+                        legalities: entry.legalities.unwrap_or_default(),
+                        unlimited_copies: unlimited_copies,
                         text: entry.text.unwrap_or_default(),
                     })
                 }
@@ -139,8 +635,8 @@ impl Database {
                 first_entry.layout = String::from("normal");
                 second_entry.layout = String::from("normal");
 
-                let first_card = try!(self.parse_card(first_entry));
-                let second_card = try!(self.parse_card(second_entry));
+                let first_card = try!(self.parse_card(first_entry, language));
+                let second_card = try!(self.parse_card(second_entry, language));
 
                 match entry.layout.as_str() {
                     "double-faced" => {
@@ -173,9 +669,87 @@ impl Database {
             _ => {
                 Ok(Card::Unimplemented {
                     name: entry.name,
+                    key: key,
                     layout: entry.layout,
                 })
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use super::{database, sanitize_name, strip_accents};
+
+    /// Ways a name might be typed that should still find it.
+    fn name_variants(name: &str) -> Vec<String> {
+        vec![String::from(name),
+             name.to_uppercase(),
+             name.to_lowercase(),
+             strip_accents(name),
+             name.replace("'", "\u{2019}"),
+             name.replace("-", "\u{2013}"),
+             name.replace(" ", "\u{a0}"),
+             format!("  {} ", name)]
+    }
+
+    #[test]
+    fn sanitize_name_folds_what_people_type() {
+        let cases = [("Yawgmoth\u{2019}s Will", "yawgmoths will"),
+                     ("Lim\u{2013}D\u{fb}l the Necromancer", "lim dul the necromancer"),
+                     ("Lim\u{2014}D\u{fb}l the Necromancer", "lim dul the necromancer"),
+                     ("Lim-D\u{fb}l the Necromancer", "lim dul the necromancer"),
+                     ("Lightning\u{a0}Bolt", "lightning bolt"),
+                     ("\u{c6}ther Vial", "aether vial"),
+                     ("\u{e6}ther vial", "aether vial"),
+                     ("  Fire   //  Ice ", "fire // ice")];
+        for &(name, key) in &cases {
+            assert_eq!(sanitize_name(name), key, "sanitizing {:?}", name);
+        }
+    }
+
+    #[test]
+    fn sanitize_name_keeps_marks_that_make_other_letters() {
+        assert_eq!(sanitize_name("\u{3ac}\u{431}\u{301}"), "\u{3b1}\u{431}");
+        assert!(sanitize_name("\u{30ac}") != sanitize_name("\u{30ab}"));
+        assert!(sanitize_name("\u{30d1}") != sanitize_name("\u{30cf}"));
+        assert_eq!(sanitize_name("\u{30ac}"), sanitize_name("\u{30ab}\u{3099}"));
+    }
+
+    /// Looks up every card name in the database, and the other halves named
+    /// by multi-part cards, the way users might type them.
+    #[test]
+    fn every_name_finds_its_card() {
+        let db = database().unwrap();
+
+        let mut names = BTreeSet::new();
+        for (_, stored) in db.entries_from("") {
+            let entry = stored.entry().unwrap();
+            for name in entry.names.iter().flat_map(|v| v.iter()) {
+                names.insert(name.clone());
+            }
+            names.insert(entry.name.clone());
+        }
+
+        let mut problems = Vec::new();
+        for name in names {
+            for variant in name_variants(&name) {
+                match db.stored(&sanitize_name(&variant)) {
+                    Some(stored) => {
+                        let entry = stored.entry().unwrap();
+                        if entry.name != name {
+                            problems.push(format!("\"{}\" finds \"{}\" instead of \"{}\"",
+                                                  variant,
+                                                  entry.name,
+                                                  name))
+                        }
+                    }
+                    None => problems.push(format!("\"{}\" doesn't find \"{}\"", variant, name)),
+                }
+            }
+        }
+        assert!(problems.is_empty(), "{}", problems.join("\n"));
+    }
+}
@@ -0,0 +1,63 @@
+// Rough text measurement, used to shrink oracle text that would otherwise be
+// cut off by the fixed size of .card_frame. All lengths are in mm and mirror
+// the sizes in results.css.
+
+/// Height available inside .card_inner for a whole card.
+pub const CARD_HEIGHT: f64 = 80.0;
+const CARD_WIDTH: f64 = 56.0;
+
+const BASE_FONT: f64 = 3.75;
+const ORACLE_FONT: f64 = 0.75;
+const NAME_WIDTH: f64 = 0.65;
+const NAME_MARGIN: f64 = 1.5;
+const PARAGRAPH_MARGIN: f64 = 1.0;
+
+// Average glyph width as a fraction of the font size. Open Sans is a bit
+// narrower than this, so the estimate errs on the side of shrinking.
+const GLYPH_WIDTH: f64 = 0.52;
+
+// Oracle text sizes to try, largest first. Index n matches the .fit_n class.
+const STEPS: [f64; 6] = [1.0, 0.9, 0.8, 0.7, 0.6, 0.5];
+
+fn line_count(text: &str, width: f64, font: f64) -> f64 {
+    let per_line = (width / (font * GLYPH_WIDTH)).floor().max(1.0);
+    (text.chars().count() as f64 / per_line).ceil().max(1.0)
+}
+
+fn oracle_height(text: &str, font: f64) -> f64 {
+    text.lines()
+        .map(|line| line_count(line, CARD_WIDTH, font) * font + PARAGRAPH_MARGIN)
+        .sum::<f64>()
+}
+
+/// Picks the .fit_n class that lets a face's oracle text fit in `height`,
+/// or an empty string if it fits at full size. `text_scale` is how much
+/// bigger the current theme draws text than results.css does.
+pub fn fit_class(name: &str,
+                 typeline: &str,
+                 text: &str,
+                 has_footer: bool,
+                 height: f64,
+                 text_scale: f64)
+                 -> String {
+    let font = BASE_FONT * text_scale;
+
+    let header = line_count(name, CARD_WIDTH * NAME_WIDTH, font) * font + NAME_MARGIN +
+                 line_count(typeline, CARD_WIDTH, font) * font + PARAGRAPH_MARGIN;
+    let footer = if has_footer {
+        font + PARAGRAPH_MARGIN
+    } else {
+        0.0
+    };
+    let available = height - header - footer;
+
+    let step = STEPS.iter()
+        .position(|&step| oracle_height(text, font * ORACLE_FONT * step) <= available)
+        .unwrap_or(STEPS.len() - 1);
+
+    if step == 0 {
+        String::new()
+    } else {
+        format!("fit_{}", step)
+    }
+}
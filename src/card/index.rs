@@ -0,0 +1,210 @@
+// The card index written by `proxygen build-db`: sorted tables of string
+// keys and byte string values, laid out so lookups can binary search the
+// file in place instead of parsing it first. All numbers are little endian
+// u32s, and offsets count from the start of the file.
+//
+//   magic, format version, data version length, data version
+//   table count, then for each table: record count, offset of its records
+//   records: key offset, key length, value offset, value length
+//   the keys and values themselves
+
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::str;
+
+use super::super::memmap::{Mmap, Protection};
+
+const MAGIC: &'static [u8] = b"PGCARDS\0";
+
+/// Bumped whenever the layout, or how values are encoded, changes. Also
+/// bumped when card names are folded into keys differently.
+const FORMAT_VERSION: u32 = 2;
+
+const RECORD_SIZE: usize = 16;
+
+fn push_u32(out: &mut Vec<u8>, n: usize) {
+    let n = n as u32;
+    out.extend_from_slice(&[n as u8, (n >> 8) as u8, (n >> 16) as u8, (n >> 24) as u8]);
+}
+
+fn read_u32(bytes: &[u8], at: usize) -> Option<usize> {
+    if at + 4 > bytes.len() {
+        return None;
+    }
+    let b = &bytes[at..at + 4];
+    Some((b[0] as u32 | (b[1] as u32) << 8 | (b[2] as u32) << 16 | (b[3] as u32) << 24) as usize)
+}
+
+/// Lays out an index. Each table has to be sorted by key, and is read back
+/// by its position in `tables`. `data_version` names the card data the
+/// index was made from, so an index for older data can be told apart.
+pub fn build(data_version: &str, tables: &[Vec<(String, Vec<u8>)>]) -> Vec<u8> {
+    let mut header = Vec::new();
+    header.extend_from_slice(MAGIC);
+    push_u32(&mut header, FORMAT_VERSION as usize);
+    push_u32(&mut header, data_version.len());
+    header.extend_from_slice(data_version.as_bytes());
+    push_u32(&mut header, tables.len());
+
+    let records_start = header.len() + tables.len() * 8;
+    let records_size: usize = tables.iter().map(|table| table.len() * RECORD_SIZE).sum();
+
+    let mut records = Vec::with_capacity(records_size);
+    let mut data = Vec::new();
+    let data_start = records_start + records_size;
+    for table in tables {
+        push_u32(&mut header, table.len());
+        push_u32(&mut header, records_start + records.len());
+        for &(ref key, ref value) in table {
+            push_u32(&mut records, data_start + data.len());
+            push_u32(&mut records, key.len());
+            data.extend_from_slice(key.as_bytes());
+            push_u32(&mut records, data_start + data.len());
+            push_u32(&mut records, value.len());
+            data.extend_from_slice(value);
+        }
+    }
+
+    header.extend(records);
+    header.extend(data);
+    header
+}
+
+/// One table of an index: where its records start and how many it has.
+#[derive(Debug, Clone, Copy)]
+struct Table {
+    records: usize,
+    len: usize,
+}
+
+/// Checks the layout of an index, so lookups can trust the offsets in it
+/// without checking them again, and finds its tables.
+fn read_tables(bytes: &[u8], data_version: &str) -> Result<Vec<Table>, String> {
+    let corrupt = || String::from("not a card index, or a damaged one");
+
+    if bytes.len() < MAGIC.len() || &bytes[..MAGIC.len()] != MAGIC {
+        return Err(corrupt());
+    }
+    let mut at = MAGIC.len();
+    if try!(read_u32(bytes, at).ok_or_else(&corrupt)) != FORMAT_VERSION as usize {
+        return Err(String::from("made by another version of proxygen, rebuild it with \
+                                 `proxygen build-db`"));
+    }
+    let version_len = try!(read_u32(bytes, at + 4).ok_or_else(&corrupt));
+    at += 8;
+    if at + version_len > bytes.len() {
+        return Err(corrupt());
+    }
+    if &bytes[at..at + version_len] != data_version.as_bytes() {
+        return Err(String::from("made from other card data, rebuild it with \
+                                 `proxygen build-db`"));
+    }
+    at += version_len;
+
+    let table_count = try!(read_u32(bytes, at).ok_or_else(&corrupt));
+    at += 4;
+    let mut tables = Vec::new();
+    for i in 0..table_count {
+        let len = try!(read_u32(bytes, at + i * 8).ok_or_else(&corrupt));
+        let records = try!(read_u32(bytes, at + i * 8 + 4).ok_or_else(&corrupt));
+        if records + len * RECORD_SIZE > bytes.len() {
+            return Err(corrupt());
+        }
+        tables.push(Table {
+            records: records,
+            len: len,
+        });
+    }
+
+    for table in &tables {
+        for i in 0..table.len {
+            let record = table.records + i * RECORD_SIZE;
+            let key = read_u32(bytes, record).unwrap();
+            let key_len = read_u32(bytes, record + 4).unwrap();
+            let value = read_u32(bytes, record + 8).unwrap();
+            let value_len = read_u32(bytes, record + 12).unwrap();
+            if key + key_len > bytes.len() || value + value_len > bytes.len() ||
+               str::from_utf8(&bytes[key..key + key_len]).is_err() {
+                return Err(corrupt());
+            }
+        }
+    }
+    Ok(tables)
+}
+
+/// A card index file, mapped into memory.
+pub struct Index {
+    mmap: Mmap,
+    tables: Vec<Table>,
+}
+
+impl Index {
+    pub fn open(path: &str, data_version: &str) -> Result<Index, String> {
+        let mmap = try!(Mmap::open_path(path, Protection::Read)
+            .map_err(|e| format!("could not open {}: {}", path, e)));
+        let tables = try!(read_tables(unsafe { mmap.as_slice() }, data_version)
+            .map_err(|e| format!("{}: {}", path, e)));
+        Ok(Index {
+            mmap: mmap,
+            tables: tables,
+        })
+    }
+
+    fn bytes(&self) -> &[u8] {
+        // The mapping is read only, so this is only unsound if the file is
+        // changed while the server runs. build-db writes a new file rather
+        // than rewriting the old one in place.
+        unsafe { self.mmap.as_slice() }
+    }
+
+    /// How many records a table has.
+    pub fn len(&self, table: usize) -> usize {
+        self.tables[table].len
+    }
+
+    /// The `i`th key of a table, in sorted order, and its value.
+    pub fn record(&self, table: usize, i: usize) -> (&str, &[u8]) {
+        let record = self.tables[table].records + i * RECORD_SIZE;
+        let bytes = self.bytes();
+        let field = |n: usize| read_u32(bytes, record + n * 4).unwrap();
+        let key = &bytes[field(0)..field(0) + field(1)];
+        let value = &bytes[field(2)..field(2) + field(3)];
+        (str::from_utf8(key).unwrap(), value)
+    }
+
+    /// Position of the first key in a table that isn't less than `key`.
+    pub fn lower_bound(&self, table: usize, key: &str) -> usize {
+        let (mut low, mut high) = (0, self.len(table));
+        while low < high {
+            let middle = low + (high - low) / 2;
+            if self.record(table, middle).0 < key {
+                low = middle + 1;
+            } else {
+                high = middle;
+            }
+        }
+        low
+    }
+
+    pub fn get(&self, table: usize, key: &str) -> Option<&[u8]> {
+        let i = self.lower_bound(table, key);
+        if i < self.len(table) {
+            let (found, value) = self.record(table, i);
+            if found == key {
+                return Some(value);
+            }
+        }
+        None
+    }
+}
+
+/// Writes an index next to `path` and then moves it there, so a server
+/// that has the old file mapped keeps reading the old contents.
+pub fn write(path: &str, bytes: &[u8]) -> io::Result<()> {
+    let temporary = format!("{}.tmp", path);
+    {
+        let mut file = try!(File::create(&temporary));
+        try!(file.write_all(bytes));
+    }
+    fs::rename(&temporary, path)
+}
@@ -0,0 +1,285 @@
+use std::fmt;
+
+/// The five colors of mana, in WUBRG order.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Color {
+    White,
+    Blue,
+    Black,
+    Red,
+    Green,
+}
+
+pub const WUBRG: [Color; 5] = [Color::White, Color::Blue, Color::Black, Color::Red, Color::Green];
+
+impl Color {
+    /// The color for a mana symbol letter, like "U" for blue.
+    pub fn from_letter(letter: &str) -> Option<Color> {
+        WUBRG.iter().find(|color| color.letter() == letter).cloned()
+    }
+
+    /// The color as the card data names it, like "Blue".
+    pub fn from_name(name: &str) -> Option<Color> {
+        WUBRG.iter().find(|color| color.name() == name).cloned()
+    }
+
+    pub fn letter(&self) -> &'static str {
+        match *self {
+            Color::White => "W",
+            Color::Blue => "U",
+            Color::Black => "B",
+            Color::Red => "R",
+            Color::Green => "G",
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Color::White => "White",
+            Color::Blue => "Blue",
+            Color::Black => "Black",
+            Color::Red => "Red",
+            Color::Green => "Green",
+        }
+    }
+}
+
+/// One symbol of a mana cost, the part between braces.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub enum ManaSymbol {
+    /// {3}
+    Generic(u64),
+    /// {U}
+    Colored(Color),
+    /// {C}
+    Colorless,
+    /// {W/U}
+    Hybrid(Color, Color),
+    /// {2/W}, payable with two generic mana instead.
+    TwoHybrid(Color),
+    /// {B/P}, payable with 2 life instead.
+    Phyrexian(Color),
+    /// {G/U/P}, payable with either color or 2 life.
+    HybridPhyrexian(Color, Color),
+    /// {HW}, half a mana, from the Un-sets.
+    Half(Color),
+    /// {X}, {Y} or {Z}.
+    Variable(String),
+    /// {S}
+    Snow,
+    /// Anything else, kept as written so it can be shown.
+    Other(String),
+}
+
+impl ManaSymbol {
+    /// Reads the inside of a symbol, like "W/U".
+    pub fn parse(symbol: &str) -> ManaSymbol {
+        if let Ok(n) = symbol.parse() {
+            return ManaSymbol::Generic(n);
+        }
+        if let Some(color) = Color::from_letter(symbol) {
+            return ManaSymbol::Colored(color);
+        }
+        if symbol.starts_with('H') {
+            if let Some(color) = Color::from_letter(&symbol[1..]) {
+                return ManaSymbol::Half(color);
+            }
+        }
+        match symbol {
+            "C" => return ManaSymbol::Colorless,
+            "S" => return ManaSymbol::Snow,
+            "X" | "Y" | "Z" => return ManaSymbol::Variable(String::from(symbol)),
+            _ => {}
+        }
+
+        let parts: Vec<&str> = symbol.split('/').collect();
+        if parts.len() == 3 && parts[2] == "P" {
+            return match (Color::from_letter(parts[0]), Color::from_letter(parts[1])) {
+                (Some(first), Some(second)) => ManaSymbol::HybridPhyrexian(first, second),
+                _ => ManaSymbol::Other(String::from(symbol)),
+            };
+        }
+        if parts.len() != 2 {
+            return ManaSymbol::Other(String::from(symbol));
+        }
+        match (parts[0], Color::from_letter(parts[0]), parts[1], Color::from_letter(parts[1])) {
+            (_, Some(first), _, Some(second)) => ManaSymbol::Hybrid(first, second),
+            ("2", _, _, Some(color)) => ManaSymbol::TwoHybrid(color),
+            (_, Some(color), "P", _) => ManaSymbol::Phyrexian(color),
+            _ => ManaSymbol::Other(String::from(symbol)),
+        }
+    }
+
+    /// What the symbol adds to the mana value. X is 0, and {2/W} counts as 2.
+    /// Half mana is really worth ½, which whole numbers can't hold, so it
+    /// rounds down to 0. Symbols that aren't known add nothing.
+    pub fn mana_value(&self) -> u64 {
+        match *self {
+            ManaSymbol::Generic(n) => n,
+            ManaSymbol::TwoHybrid(_) => 2,
+            ManaSymbol::Variable(_) |
+            ManaSymbol::Half(_) |
+            ManaSymbol::Other(_) => 0,
+            ManaSymbol::Colored(_) |
+            ManaSymbol::Colorless |
+            ManaSymbol::Hybrid(_, _) |
+            ManaSymbol::Phyrexian(_) |
+            ManaSymbol::HybridPhyrexian(_, _) |
+            ManaSymbol::Snow => 1,
+        }
+    }
+
+    pub fn colors(&self) -> Vec<Color> {
+        match *self {
+            ManaSymbol::Colored(color) |
+            ManaSymbol::TwoHybrid(color) |
+            ManaSymbol::Phyrexian(color) |
+            ManaSymbol::Half(color) => vec![color],
+            ManaSymbol::Hybrid(first, second) |
+            ManaSymbol::HybridPhyrexian(first, second) => vec![first, second],
+            _ => Vec::new(),
+        }
+    }
+}
+
+impl fmt::Display for ManaSymbol {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ManaSymbol::Generic(n) => write!(f, "{{{}}}", n),
+            ManaSymbol::Colored(color) => write!(f, "{{{}}}", color.letter()),
+            ManaSymbol::Colorless => write!(f, "{{C}}"),
+            ManaSymbol::Hybrid(first, second) => {
+                write!(f, "{{{}/{}}}", first.letter(), second.letter())
+            }
+            ManaSymbol::TwoHybrid(color) => write!(f, "{{2/{}}}", color.letter()),
+            ManaSymbol::Phyrexian(color) => write!(f, "{{{}/P}}", color.letter()),
+            ManaSymbol::HybridPhyrexian(first, second) => {
+                write!(f, "{{{}/{}/P}}", first.letter(), second.letter())
+            }
+            ManaSymbol::Half(color) => write!(f, "{{H{}}}", color.letter()),
+            ManaSymbol::Snow => write!(f, "{{S}}"),
+            ManaSymbol::Variable(ref s) |
+            ManaSymbol::Other(ref s) => write!(f, "{{{}}}", s),
+        }
+    }
+}
+
+/// A mana cost, like "{2}{U}{U}", read into its symbols.
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct ManaCost {
+    pub symbols: Vec<ManaSymbol>,
+}
+
+impl ManaCost {
+    /// Reads a cost as the card data writes it. Text outside braces is
+    /// ignored.
+    pub fn parse(cost: &str) -> ManaCost {
+        ManaCost {
+            symbols: cost.split('{')
+                .skip(1)
+                .filter_map(|part| part.split('}').next())
+                .map(ManaSymbol::parse)
+                .collect(),
+        }
+    }
+
+    /// The cost with another one after it, like the two halves of a split
+    /// card.
+    pub fn and(&self, other: &ManaCost) -> ManaCost {
+        ManaCost { symbols: self.symbols.iter().chain(&other.symbols).cloned().collect() }
+    }
+
+    /// The mana value, also known as converted mana cost.
+    pub fn mana_value(&self) -> u64 {
+        self.symbols.iter().map(ManaSymbol::mana_value).sum()
+    }
+
+    /// The colors of the symbols, in WUBRG order.
+    pub fn colors(&self) -> Vec<Color> {
+        let mut colors: Vec<Color> = self.symbols.iter().flat_map(|s| s.colors()).collect();
+        colors.sort();
+        colors.dedup();
+        colors
+    }
+
+    /// Whether every colored symbol is a two-color hybrid, like {W/U} or
+    /// {G/U/P}.
+    pub fn is_hybrid(&self) -> bool {
+        let mut colored = self.symbols.iter().filter(|s| !s.colors().is_empty()).peekable();
+        colored.peek().is_some() &&
+        colored.all(|s| match *s {
+            ManaSymbol::Hybrid(_, _) |
+            ManaSymbol::HybridPhyrexian(_, _) => true,
+            _ => false,
+        })
+    }
+}
+
+impl fmt::Display for ManaCost {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for symbol in &self.symbols {
+            try!(write!(f, "{}", symbol));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Color, ManaCost, ManaSymbol};
+
+    #[test]
+    fn parse_reads_every_kind_of_symbol() {
+        let cases = [("3", ManaSymbol::Generic(3)),
+                     ("U", ManaSymbol::Colored(Color::Blue)),
+                     ("C", ManaSymbol::Colorless),
+                     ("W/U", ManaSymbol::Hybrid(Color::White, Color::Blue)),
+                     ("2/W", ManaSymbol::TwoHybrid(Color::White)),
+                     ("B/P", ManaSymbol::Phyrexian(Color::Black)),
+                     ("G/U/P", ManaSymbol::HybridPhyrexian(Color::Green, Color::Blue)),
+                     ("HW", ManaSymbol::Half(Color::White)),
+                     ("X", ManaSymbol::Variable(String::from("X"))),
+                     ("S", ManaSymbol::Snow),
+                     ("Q", ManaSymbol::Other(String::from("Q"))),
+                     ("G/Q/P", ManaSymbol::Other(String::from("G/Q/P")))];
+        for &(text, ref symbol) in &cases {
+            assert_eq!(&ManaSymbol::parse(text), symbol, "parsing {{{}}}", text);
+            assert_eq!(symbol.to_string(), format!("{{{}}}", text));
+        }
+    }
+
+    #[test]
+    fn mana_value_and_colors() {
+        let cases = [("{2/W}", 2, vec![Color::White]),
+                     ("{G/U/P}", 1, vec![Color::Blue, Color::Green]),
+                     ("{HW}", 0, vec![Color::White]),
+                     ("{X}{R}", 1, vec![Color::Red]),
+                     ("{S}", 1, vec![]),
+                     ("{C}", 1, vec![]),
+                     ("{Q}", 0, vec![])];
+        for &(text, mana_value, ref colors) in &cases {
+            let cost = ManaCost::parse(text);
+            assert_eq!(cost.mana_value(), mana_value, "mana value of {}", text);
+            assert_eq!(&cost.colors(), colors, "colors of {}", text);
+        }
+    }
+
+    #[test]
+    fn split_card_costs_add_up() {
+        // Fire // Ice
+        let cost = ManaCost::parse("{1}{R}").and(&ManaCost::parse("{1}{U}"));
+        assert_eq!(cost.mana_value(), 4);
+        assert_eq!(cost.colors(), vec![Color::Blue, Color::Red]);
+        assert_eq!(cost.to_string(), "{1}{R}{1}{U}");
+        assert!(!cost.is_hybrid());
+    }
+
+    #[test]
+    fn is_hybrid_needs_every_colored_symbol_hybrid() {
+        assert!(ManaCost::parse("{2}{W/U}{W/U}").is_hybrid());
+        assert!(ManaCost::parse("{G/U/P}").is_hybrid());
+        assert!(!ManaCost::parse("{W/U}{U}").is_hybrid());
+        assert!(!ManaCost::parse("{2/W}").is_hybrid());
+        assert!(!ManaCost::parse("{3}").is_hybrid());
+    }
+}
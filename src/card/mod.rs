@@ -1,45 +1,92 @@
+use std::sync::Arc;
+
 use super::error::ProxygenError;
+use super::i18n::{fill, CardLanguage, Language, Strings};
 
 use super::maud::PreEscaped;
 
 use super::regex::Regex;
 
 mod database;
-use self::database::DATABASE;
+use self::database::database;
+pub use self::database::{card_key, complete, sanitize_name, save_index, search, use_index,
+                         Legality, Search};
+mod fit;
+use self::fit::CARD_HEIGHT;
+mod index;
+mod mana;
+pub use self::mana::{Color, ManaCost, WUBRG};
+
+/// The main card types, as the card data names them. Cards are sorted by
+/// type and counted on the cover sheet in this order.
+pub const TYPES: [&'static str; 7] = ["Creature",
+                                      "Planeswalker",
+                                      "Instant",
+                                      "Sorcery",
+                                      "Enchantment",
+                                      "Artifact",
+                                      "Land"];
 
 #[derive(Deserialize, Debug)]
 pub enum Card {
     Creature {
         name: String,
-        manacost: String,
+        /// The database key of the English name, whatever language the
+        /// card is in.
+        key: String,
+        manacost: ManaCost,
+        colors: Vec<Color>,
         typeline: String,
+        /// The card types, in English whatever language the card is in.
+        types: Vec<String>,
+        legalities: Vec<Legality>,
+        unlimited_copies: bool,
         text: String,
         power: String,
         toughness: String,
     },
     Planeswalker {
         name: String,
-        manacost: String,
+        /// The database key of the English name, whatever language the
+        /// card is in.
+        key: String,
+        manacost: ManaCost,
+        colors: Vec<Color>,
         typeline: String,
+        /// The card types, in English whatever language the card is in.
+        types: Vec<String>,
+        legalities: Vec<Legality>,
+        unlimited_copies: bool,
         text: String,
         loyalty: u64,
     },
     Noncreature {
         name: String,
-        manacost: String,
+        /// The database key of the English name, whatever language the
+        /// card is in.
+        key: String,
+        manacost: ManaCost,
+        colors: Vec<Color>,
         typeline: String,
+        /// The card types, in English whatever language the card is in.
+        types: Vec<String>,
+        legalities: Vec<Legality>,
+        unlimited_copies: bool,
         text: String,
     },
     DoubleFaced { front: Box<Card>, back: Box<Card> },
     Split { left: Box<Card>, right: Box<Card> },
     Flip { top: Box<Card>, bottom: Box<Card> },
     Meld { front: Box<Card>, back: Box<Card> },
-    Unimplemented { name: String, layout: String },
+    Unimplemented {
+        name: String,
+        key: String,
+        layout: String,
+    },
 }
 
 lazy_static!{
     static ref ORACLE_RE: Regex = Regex::new(r"(?P<reminder>\(.+\))").unwrap();
-    static ref MANACOST_RE: Regex = Regex::new(r"(?P<symbol>\{.+?\})").unwrap();
 }
 
 fn prettify_oracle_text(text: &str) -> String {
@@ -49,114 +96,309 @@ fn prettify_oracle_text(text: &str) -> String {
         .collect()
 }
 
-fn break_manacost(manacost: &str) -> String {
-    MANACOST_RE.replace_all(manacost, "$symbol<wbr>")
+fn break_manacost(manacost: &ManaCost) -> String {
+    manacost.symbols.iter().map(|symbol| format!("{}<wbr>", symbol)).collect()
+}
+
+fn frame_class(colors: &[Color], manacost: &ManaCost, types: &[String]) -> String {
+    match colors.len() {
+        0 => {
+            if types.iter().any(|t| t == "Land") {
+                String::from("frame_land")
+            } else if types.iter().any(|t| t == "Artifact") {
+                String::from("frame_artifact")
+            } else {
+                String::from("frame_colorless")
+            }
+        }
+        1 => format!("frame_{}", colors[0].letter().to_lowercase()),
+        2 if manacost.is_hybrid() => {
+            format!("frame_{}{}",
+                    colors[0].letter().to_lowercase(),
+                    colors[1].letter().to_lowercase())
+        }
+        _ => String::from("frame_gold"),
+    }
 }
 
-fn base_inner_html(name: &str, manacost: &str, typeline: &str, text: &str) -> String {
+fn base_inner_html(name: &str,
+                   manacost: &ManaCost,
+                   typeline: &str,
+                   text: &str,
+                   frame: &str,
+                   fit: &str)
+                   -> String {
     let pretty_text = prettify_oracle_text(text);
     let breaklined_manacost = break_manacost(manacost);
     let mut s = String::new();
     html!( s,
-        div class="name_mana_line" {
+        div class=(format!("name_mana_line {}", frame)) {
             p class="name" { (name) }
             p class="manacost" { (PreEscaped(breaklined_manacost)) }
         }
         p class="typeline" { (typeline) }
-        div class="oracle_div" { (PreEscaped(pretty_text)) }
+        div class="oracle_div" {
+            @if fit.is_empty() {
+                (PreEscaped(pretty_text))
+            } @else {
+                div class=(fit) { (PreEscaped(pretty_text)) }
+            }
+        }
     )
         .unwrap();
     s
 }
 
 impl Card {
-    pub fn from_name(name: &str) -> Result<Card, ProxygenError> {
-        DATABASE.get(name)
+    /// Looks the card up by name. Cards are shared, so looking the same
+    /// card up again is cheap.
+    pub fn from_name(name: &str) -> Result<Arc<Card>, ProxygenError> {
+        try!(database()).get(name)
+    }
+
+    /// Looks the card up by its English name or a translated one, to be
+    /// drawn in `language` where the card data has it.
+    pub fn from_name_in(name: &str, language: CardLanguage) -> Result<Arc<Card>, ProxygenError> {
+        let db = try!(database());
+        match language {
+            CardLanguage::AsListed => db.get_as_listed(name),
+            CardLanguage::In(Language::English) => db.get(name),
+            CardLanguage::In(language) => db.get_in(name, language.card_data_name()),
+        }
+    }
+
+    /// The name the card goes by in decklists, "Fire // Ice" for split cards.
+    pub fn name(&self) -> String {
+        match *self {
+            Card::Creature { ref name, .. } |
+            Card::Planeswalker { ref name, .. } |
+            Card::Noncreature { ref name, .. } |
+            Card::Unimplemented { ref name, .. } => name.clone(),
+            Card::Split { ref left, ref right } => format!("{} // {}", left.name(), right.name()),
+            Card::DoubleFaced { ref front, .. } |
+            Card::Meld { ref front, .. } |
+            Card::Flip { top: ref front, .. } => front.name(),
+        }
+    }
+
+    /// What the card is known by whichever name it was listed by or is
+    /// drawn with, for telling whether two entries are the same card. Cards
+    /// with more than one part go by their front face's.
+    pub fn key(&self) -> &str {
+        match *self {
+            Card::Creature { ref key, .. } |
+            Card::Planeswalker { ref key, .. } |
+            Card::Noncreature { ref key, .. } |
+            Card::Unimplemented { ref key, .. } => key,
+            Card::Split { left: ref front, .. } |
+            Card::DoubleFaced { ref front, .. } |
+            Card::Meld { ref front, .. } |
+            Card::Flip { top: ref front, .. } => front.key(),
+        }
+    }
+
+    /// The card's colors, in WUBRG order.
+    pub fn colors(&self) -> Vec<Color> {
+        match *self {
+            Card::Creature { ref colors, .. } |
+            Card::Planeswalker { ref colors, .. } |
+            Card::Noncreature { ref colors, .. } => colors.clone(),
+            Card::Split { ref left, ref right } => {
+                let mut colors = left.colors();
+                colors.extend(right.colors());
+                colors.sort();
+                colors.dedup();
+                colors
+            }
+            Card::DoubleFaced { ref front, .. } |
+            Card::Meld { ref front, .. } |
+            Card::Flip { top: ref front, .. } => front.colors(),
+            Card::Unimplemented { .. } => Vec::new(),
+        }
+    }
+
+    /// The card's legality in a format, as the card data names both, or None
+    /// if it isn't legal there.
+    pub fn legality(&self, format: &str) -> Option<&str> {
+        match *self {
+            Card::Creature { ref legalities, .. } |
+            Card::Planeswalker { ref legalities, .. } |
+            Card::Noncreature { ref legalities, .. } => {
+                legalities.iter()
+                    .find(|l| l.format == format)
+                    .map(|l| l.legality.as_str())
+            }
+            Card::Split { left: ref front, .. } |
+            Card::DoubleFaced { ref front, .. } |
+            Card::Meld { ref front, .. } |
+            Card::Flip { top: ref front, .. } => front.legality(format),
+            Card::Unimplemented { .. } => None,
+        }
+    }
+
+    /// Whether a deck may have any number of copies: basic lands, and cards
+    /// like Relentless Rats that say so.
+    pub fn unlimited_copies(&self) -> bool {
+        match *self {
+            Card::Creature { unlimited_copies, .. } |
+            Card::Planeswalker { unlimited_copies, .. } |
+            Card::Noncreature { unlimited_copies, .. } => unlimited_copies,
+            Card::Split { left: ref front, .. } |
+            Card::DoubleFaced { ref front, .. } |
+            Card::Meld { ref front, .. } |
+            Card::Flip { top: ref front, .. } => front.unlimited_copies(),
+            Card::Unimplemented { .. } => false,
+        }
+    }
+
+    /// The mana cost of the front face. Split cards have both halves' costs.
+    pub fn mana_cost(&self) -> ManaCost {
+        match *self {
+            Card::Creature { ref manacost, .. } |
+            Card::Planeswalker { ref manacost, .. } |
+            Card::Noncreature { ref manacost, .. } => manacost.clone(),
+            Card::Split { ref left, ref right } => left.mana_cost().and(&right.mana_cost()),
+            Card::DoubleFaced { ref front, .. } |
+            Card::Meld { ref front, .. } |
+            Card::Flip { top: ref front, .. } => front.mana_cost(),
+            Card::Unimplemented { .. } => ManaCost::default(),
+        }
     }
 
+    /// The mana value, also known as converted mana cost.
+    pub fn mana_value(&self) -> u64 {
+        self.mana_cost().mana_value()
+    }
+
+    /// The card types of the front face, like "Artifact" and "Creature".
+    pub fn types(&self) -> Vec<String> {
+        match *self {
+            Card::Creature { ref types, .. } |
+            Card::Planeswalker { ref types, .. } |
+            Card::Noncreature { ref types, .. } => types.clone(),
+            Card::Split { left: ref front, .. } |
+            Card::DoubleFaced { ref front, .. } |
+            Card::Meld { ref front, .. } |
+            Card::Flip { top: ref front, .. } => front.types(),
+            Card::Unimplemented { .. } => Vec::new(),
+        }
+    }
+
+    pub fn is_land(&self) -> bool {
+        self.types().iter().any(|t| t == "Land")
+    }
+
+    /// CSS classes describing the card's color identity, used by the optional
+    /// tinted frames stylesheet. Unstyled unless that stylesheet is included.
+    fn frame_class(&self) -> String {
+        frame_class(&self.colors(), &self.mana_cost(), &self.types())
+    }
+
+    /// Renders a single face. `height` is the room the face has inside the
+    /// card frame, used to shrink long oracle text so it isn't cut off.
     #[allow(cyclomatic_complexity)]
-    fn inner_html(&self) -> String {
+    fn inner_html(&self,
+                  height: f64,
+                  text_scale: f64,
+                  strings: &Strings)
+                  -> Result<String, ProxygenError> {
+        let frame = self.frame_class();
         match *self {
             Card::Creature { ref name,
                              ref manacost,
                              ref typeline,
                              ref text,
                              ref power,
-                             ref toughness } => {
+                             ref toughness,
+                             .. } => {
+                let fit = fit::fit_class(name, typeline, text, true, height, text_scale);
                 let mut s = String::new();
                 html!( s,
-                    (PreEscaped(base_inner_html(name, manacost, typeline, text)))
+                    (PreEscaped(base_inner_html(name, manacost, typeline, text, &frame, &fit)))
                     p class = "power_toughness" { (power) "/" (toughness) }
                 )
                     .unwrap();
-                s
+                Ok(s)
             }
-            Card::Planeswalker { ref name, ref manacost, ref typeline, ref text, ref loyalty } => {
+            Card::Planeswalker { ref name,
+                                 ref manacost,
+                                 ref typeline,
+                                 ref text,
+                                 ref loyalty,
+                                 .. } => {
+                let fit = fit::fit_class(name, typeline, text, true, height, text_scale);
                 let mut s = String::new();
                 html!( s,
-                    (PreEscaped(base_inner_html(name, manacost, typeline, text)))
+                    (PreEscaped(base_inner_html(name, manacost, typeline, text, &frame, &fit)))
                     p class = "loyalty" { (loyalty) }
                 )
                     .unwrap();
-                s
+                Ok(s)
             }
-            Card::Noncreature { ref name, ref manacost, ref typeline, ref text } => {
+            Card::Noncreature { ref name, ref manacost, ref typeline, ref text, .. } => {
+                let fit = fit::fit_class(name, typeline, text, false, height, text_scale);
                 let mut s = String::new();
                 html!(s,
-                      (PreEscaped(base_inner_html(name, manacost, typeline, text))))
+                      (PreEscaped(base_inner_html(name, manacost, typeline, text, &frame, &fit))))
                     .unwrap();
-                s
+                Ok(s)
             }
-            Card::Unimplemented { ref name, ref layout } => {
+            Card::Unimplemented { ref name, ref layout, .. } => {
                 let mut s = String::new();
                 html!( s,
                     p class="name" { (name) }
                     div class="oracle_div" {
-                        p class="oracle_p" {
-                            "This type of card (" (layout) ") is not yet implemented."
-                        }
-                        p class="oracle_p" {
-                            "Go complain to the developer"
-                        }
+                        p class="oracle_p" { (fill(strings.unimplemented, &[layout])) }
+                        p class="oracle_p" { (strings.complain) }
                     }
                 )
                     .unwrap();
-                s
+                Ok(s)
             }
-            ref err => panic!("This shouldn't happen. {:?}", err),
+            // Only reached for a face of a multi-part card that is itself multi-part.
+            _ => Err(ProxygenError::NestedMulticard(self.name())),
         }
     }
 
-    pub fn to_html(&self) -> String {
+    /// Renders the card as one or more card-sized frames. `text_scale` is how
+    /// much larger than results.css the current theme draws text. `strings`
+    /// are for the placeholder drawn for cards that can't be rendered yet.
+    pub fn to_html(&self, text_scale: f64, strings: &Strings) -> Result<String, ProxygenError> {
+        // Split and flip halves share one frame, minus the divider between them.
+        let half_height = (CARD_HEIGHT - 1.5) / 2.0;
+        let frame_class = format!("card_frame {}", self.frame_class());
         match *self {
             Card::DoubleFaced { ref front, ref back } |
             Card::Meld { ref front, ref back } => {
-                let front_html = front.inner_html();
-                let back_html = back.inner_html();
+                let front_html = try!(front.inner_html(CARD_HEIGHT, text_scale, strings));
+                let back_html = try!(back.inner_html(CARD_HEIGHT, text_scale, strings));
+
+                let front_class = format!("card_frame {}", front.frame_class());
+                let back_class = format!("card_frame {}", back.frame_class());
 
                 let mut s = String::new();
                 html!(s,
-                      div class="card_frame" {
+                      div class=(front_class) {
                           div class="card_inner" {
                             (PreEscaped(front_html))
                           }
                       }
-                    div class="card_frame" {
+                    div class=(back_class) {
                         div class="card_inner" {
                           (PreEscaped(back_html))
                         }
                     }
                 )
                     .unwrap();
-                s
+                Ok(s)
             }
             Card::Split { ref left, ref right } => {
-                let left_html = left.inner_html();
-                let right_html = right.inner_html();
+                let left_html = try!(left.inner_html(half_height, text_scale, strings));
+                let right_html = try!(right.inner_html(half_height, text_scale, strings));
 
                 let mut s = String::new();
                 html!(s,
-                    div class="card_frame" {
+                    div class=(frame_class) {
                         div class="card_inner" {
                             div class="split_left" {
                                 (PreEscaped(left_html))
@@ -168,15 +410,15 @@ impl Card {
                     }
                 )
                     .unwrap();
-                s
+                Ok(s)
             }
             Card::Flip { ref top, ref bottom } => {
-                let top_html = top.inner_html();
-                let bottom_html = bottom.inner_html();
+                let top_html = try!(top.inner_html(half_height, text_scale, strings));
+                let bottom_html = try!(bottom.inner_html(half_height, text_scale, strings));
 
                 let mut s = String::new();
                 html!(s,
-                    div class="card_frame" {
+                    div class=(frame_class) {
                         div class="card_inner" {
                             div class="flip_top" {
                                 (PreEscaped(top_html))
@@ -188,19 +430,21 @@ impl Card {
                     }
                 )
                     .unwrap();
-                s
+                Ok(s)
             }
             _ => {
+                let inner_html = try!(self.inner_html(CARD_HEIGHT, text_scale, strings));
+
                 let mut s = String::new();
                 html!( s,
-                    div class="card_frame" {
+                    div class=(frame_class) {
                         div class="card_inner" {
-                            (PreEscaped(self.inner_html()))
+                            (PreEscaped(inner_html))
                         }
                     }
                 )
                     .unwrap();
-                s
+                Ok(s)
             }
         }
     }
@@ -0,0 +1,119 @@
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::process::{Command, Stdio};
+
+use super::clap::ArgMatches;
+
+use super::card;
+use super::collection::Collection;
+use super::config::Config;
+use super::decklist::parse_decklist;
+use super::error::ProxygenError;
+use super::format::{self, Format};
+use super::i18n::{CardLanguage, Language};
+use super::render::{self, RenderOptions};
+use super::sort::{self, Order};
+use super::stats::DeckStats;
+use super::theme::Theme;
+
+fn read_file(path: &str) -> Result<String, ProxygenError> {
+    let mut decklist = String::new();
+    if path == "-" {
+        let stdin = io::stdin();
+        try!(stdin.lock().read_to_string(&mut decklist));
+    } else {
+        let mut file = try!(File::open(path));
+        try!(file.read_to_string(&mut decklist));
+    }
+    Ok(decklist)
+}
+
+fn write_pdf(doc: &str, path: &str) -> Result<(), ProxygenError> {
+    // "-" makes wkhtmltopdf read the page from stdin.
+    let mut child = try!(Command::new("wkhtmltopdf")
+        .args(&["--quiet", "-", path])
+        .stdin(Stdio::piped())
+        .spawn()
+        .map_err(|e| {
+            ProxygenError::PdfConversionFailed(format!("could not run wkhtmltopdf: {}", e))
+        }));
+
+    try!(child.stdin.take().unwrap().write_all(doc.as_bytes()));
+
+    let status = try!(child.wait());
+    if status.success() {
+        Ok(())
+    } else {
+        Err(ProxygenError::PdfConversionFailed(format!("wkhtmltopdf exited with {}", status)))
+    }
+}
+
+/// `proxygen render`: parses a decklist file and writes the proxy sheet,
+/// the same one POST /proxygen would send back.
+pub fn render(config: &Config, matches: &ArgMatches) -> Result<(), ProxygenError> {
+    let decklist = try!(read_file(matches.value_of("DECKLIST").unwrap()));
+
+    let options = RenderOptions {
+        theme: matches.value_of("theme")
+            .and_then(Theme::from_param)
+            .unwrap_or(config.default_theme),
+        color_frames: matches.is_present("color-frames"),
+        language: Language::default(),
+        card_language: if matches.is_present("localize") {
+            CardLanguage::AsListed
+        } else {
+            matches.value_of("card-language")
+                .and_then(CardLanguage::from_param)
+                .unwrap_or_default()
+        },
+        format: matches.value_of("format").and_then(Format::from_param),
+        stats: matches.is_present("stats"),
+        order: matches.value_of("order").and_then(Order::from_param).unwrap_or_default(),
+        inline_assets: true,
+    };
+
+    let sections = try!(parse_decklist(&decklist, config.max_cards, options.card_language));
+
+    let mut reports = Vec::new();
+    if let Some(format) = options.format {
+        reports.push(format::report(format, &sections, options.language.strings()));
+    }
+    let stats = if options.stats {
+        Some(DeckStats::new(&sections))
+    } else {
+        None
+    };
+    let mut cards = sort::arrange(sections, options.order);
+
+    if let Some(path) = matches.value_of("collection") {
+        let collection = Collection::parse(&try!(read_file(path)));
+        let (remaining, skipped) = collection.subtract(cards);
+        for (n, name) in skipped {
+            try!(writeln!(io::stderr(), "Skipped {} {}, already owned", n, name));
+        }
+        cards = remaining;
+    }
+    let doc = try!(render::results_page(&cards, &options, None, &reports, stats.as_ref()));
+
+    match matches.value_of("output") {
+        Some(path) if path.ends_with(".pdf") => try!(write_pdf(&doc, path)),
+        Some(path) => {
+            let mut file = try!(File::create(path));
+            try!(file.write_all(doc.as_bytes()));
+        }
+        None => {
+            let stdout = io::stdout();
+            try!(stdout.lock().write_all(doc.as_bytes()));
+        }
+    }
+    Ok(())
+}
+
+/// `proxygen build-db`: saves the built-in card data as a card index, which
+/// later runs map into memory instead of parsing the card data again.
+pub fn build_db(config: &Config, matches: &ArgMatches) -> Result<(), ProxygenError> {
+    let path = matches.value_of("OUTPUT").unwrap_or(&config.card_index);
+    let size = try!(card::save_index(path));
+    println!("Wrote {} ({} bytes)", path, size);
+    Ok(())
+}
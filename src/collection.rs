@@ -0,0 +1,121 @@
+use std::cmp;
+use std::collections::BTreeMap;
+use std::mem;
+use std::sync::Arc;
+
+use super::card::{card_key, sanitize_name, Card};
+use super::decklist::{front_name, split_line};
+
+// Header names used for the name and count columns by the CSV exports of
+// common collection managers (Deckbox, TCGplayer, MTGGoldfish, ...).
+const NAME_COLUMNS: [&'static str; 3] = ["name", "card name", "card"];
+const COUNT_COLUMNS: [&'static str; 4] = ["count", "quantity", "qty", "amount"];
+
+/// Cards someone already owns, keyed like `Card::key` so that translated
+/// names and the same spelling variants that work in decklists match here
+/// too.
+#[derive(Debug, Default)]
+pub struct Collection {
+    owned: BTreeMap<String, u64>,
+}
+
+/// Names that aren't cards are still kept, they just won't match anything.
+fn key(name: &str) -> String {
+    let name = front_name(name.trim());
+    card_key(name).unwrap_or_else(|| sanitize_name(name))
+}
+
+fn csv_fields(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(mem::replace(&mut field, String::new())),
+            c => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields
+}
+
+fn find_column(header: &[String], names: &[&str]) -> Option<usize> {
+    header.iter().position(|column| names.contains(&column.trim().to_lowercase().as_str()))
+}
+
+impl Collection {
+    /// Reads a collection, either as a CSV export with a header row naming
+    /// its columns, or as a plain list in the same format as decklists.
+    pub fn parse(text: &str) -> Collection {
+        let mut collection = Collection::default();
+        let mut lines = text.lines().filter(|line| !line.trim().is_empty()).peekable();
+
+        let header = lines.peek().map(|line| csv_fields(line)).unwrap_or_default();
+        match find_column(&header, &NAME_COLUMNS) {
+            Some(name_column) => {
+                let count_column = find_column(&header, &COUNT_COLUMNS);
+                lines.next();
+                for line in lines {
+                    let fields = csv_fields(line);
+                    let name = match fields.get(name_column) {
+                        Some(v) => v,
+                        None => continue,
+                    };
+                    let count = count_column.and_then(|i| fields.get(i))
+                        .and_then(|v| v.trim().parse().ok())
+                        .unwrap_or(1);
+                    collection.add(name, count);
+                }
+            }
+            None => {
+                for line in lines {
+                    if let Some((count, name)) = split_line(line.trim()) {
+                        let count = count.and_then(|v| v.parse().ok()).unwrap_or(1);
+                        collection.add(name, count);
+                    }
+                }
+            }
+        }
+        collection
+    }
+
+    fn add(&mut self, name: &str, count: u64) {
+        let owned = self.owned.entry(key(name)).or_insert(0);
+        *owned = owned.saturating_add(count);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.owned.is_empty()
+    }
+
+    /// Takes owned copies out of a resolved decklist. Returns the cards that
+    /// still need proxies, and the names and counts that were skipped.
+    pub fn subtract(&self,
+                    cards: Vec<(u64, Arc<Card>)>)
+                    -> (Vec<(u64, Arc<Card>)>, Vec<(u64, String)>) {
+        let mut available = self.owned.clone();
+        let mut remaining = Vec::new();
+        let mut skipped = Vec::new();
+
+        for (n, card) in cards {
+            let owned = available.entry(String::from(card.key())).or_insert(0);
+            let used = cmp::min(*owned, n);
+            *owned -= used;
+
+            if used > 0 {
+                skipped.push((used, card.name()));
+            }
+            if n > used {
+                remaining.push((n - used, card));
+            }
+        }
+        (remaining, skipped)
+    }
+}
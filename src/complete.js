@@ -0,0 +1,85 @@
+// Suggests card names for the decklist line being typed, from
+// /proxygen/api/complete. Picking a suggestion replaces the name on that
+// line, keeping its count.
+(function () {
+    var decklist = document.getElementById("decklist");
+    var suggestions = document.getElementById("suggestions");
+    if (!decklist || !suggestions) {
+        return;
+    }
+
+    // Count and "SB:" marker at the start of a line, which are kept as typed.
+    var LINE_START = /^(\s*(SB:\s*)?(\d+x?\s*)?)(.*)$/i;
+    // Answers can arrive out of order. Only the latest request's is shown.
+    var latest = 0;
+
+    // The line the cursor is on, and where it starts and ends.
+    function currentLine() {
+        var text = decklist.value;
+        var cursor = decklist.selectionStart;
+        var start = text.lastIndexOf("\n", cursor - 1) + 1;
+        var end = text.indexOf("\n", cursor);
+        if (end === -1) {
+            end = text.length;
+        }
+        return { start: start, end: end, text: text.slice(start, end) };
+    }
+
+    function clear() {
+        suggestions.innerHTML = "";
+    }
+
+    function choose(name) {
+        var line = currentLine();
+        var parts = LINE_START.exec(line.text);
+        var text = decklist.value;
+        var replacement = parts[1] + name;
+        decklist.value = text.slice(0, line.start) + replacement + text.slice(line.end);
+        var cursor = line.start + replacement.length;
+        decklist.setSelectionRange(cursor, cursor);
+        decklist.focus();
+        clear();
+    }
+
+    function show(names) {
+        clear();
+        names.forEach(function (name) {
+            var item = document.createElement("li");
+            item.textContent = name;
+            // mousedown rather than click, so the textarea keeps its cursor.
+            item.addEventListener("mousedown", function (event) {
+                event.preventDefault();
+                choose(name);
+            });
+            suggestions.appendChild(item);
+        });
+    }
+
+    decklist.addEventListener("input", function () {
+        var name = LINE_START.exec(currentLine().text)[4].trim();
+        var requestNumber = ++latest;
+        if (name.length < 2) {
+            clear();
+            return;
+        }
+
+        var request = new XMLHttpRequest();
+        request.open("GET", "/proxygen/api/complete?q=" + encodeURIComponent(name));
+        request.setRequestHeader("Accept", "application/json");
+        request.onload = function () {
+            if (requestNumber !== latest || request.status !== 200) {
+                return;
+            }
+            var names = JSON.parse(request.responseText);
+            // Nothing to suggest once the name is typed out in full.
+            if (names.length === 1 && names[0] === name) {
+                clear();
+            } else {
+                show(names);
+            }
+        };
+        request.send();
+    });
+
+    decklist.addEventListener("blur", clear);
+})();
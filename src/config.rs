@@ -0,0 +1,139 @@
+use std::env;
+use std::fs::File;
+use std::io::Read;
+use std::str::FromStr;
+
+use super::clap::ArgMatches;
+use super::toml::{Parser, Value};
+
+use super::error::ProxygenError;
+use super::theme::Theme;
+
+/// Server settings. Each setting is read from, in increasing priority: the
+/// built-in default, the TOML file given by --config or PROXYGEN_CONFIG, its
+/// environment variable and its command-line flag.
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub address: String,
+    pub port: u16,
+    pub max_cards: u64,
+    pub max_body_size: u64,
+    pub default_theme: Theme,
+    pub decks_dir: String,
+    /// Card index made by `proxygen build-db`. Used if the file exists.
+    pub card_index: String,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            address: String::from("127.0.0.1"),
+            port: 6767,
+            max_cards: 1000,
+            max_body_size: 256 * 1024,
+            default_theme: Theme::Standard,
+            decks_dir: String::from("decks"),
+            card_index: String::from("cards.idx"),
+        }
+    }
+}
+
+// (TOML key, environment variable, command-line flag)
+const SETTINGS: [(&'static str, &'static str, &'static str); 7] =
+    [("address", "PROXYGEN_ADDRESS", "address"),
+     ("port", "PROXYGEN_PORT", "port"),
+     ("max_cards", "PROXYGEN_MAX_CARDS", "max-cards"),
+     ("max_body_size", "PROXYGEN_MAX_BODY_SIZE", "max-body-size"),
+     ("default_theme", "PROXYGEN_THEME", "default-theme"),
+     ("decks_dir", "PROXYGEN_DECKS_DIR", "decks-dir"),
+     ("card_index", "PROXYGEN_CARD_INDEX", "card-index")];
+
+fn parse_number<T: FromStr>(key: &str, value: &str) -> Result<T, ProxygenError> {
+    value.parse().map_err(|_| {
+        ProxygenError::ConfigError(format!("{} must be a number, got {:?}", key, value))
+    })
+}
+
+impl Config {
+    pub fn load(matches: &ArgMatches) -> Result<Config, ProxygenError> {
+        let mut config = Config::default();
+
+        let config_path = matches.value_of("config")
+            .map(String::from)
+            .or_else(|| env::var("PROXYGEN_CONFIG").ok());
+        if let Some(path) = config_path {
+            try!(config.read_file(&path));
+        }
+
+        for &(key, var, _) in SETTINGS.iter() {
+            if let Ok(value) = env::var(var) {
+                try!(config.set(key, &value));
+            }
+        }
+
+        for &(key, _, flag) in SETTINGS.iter() {
+            if let Some(value) = matches.value_of(flag) {
+                try!(config.set(key, value));
+            }
+        }
+
+        Ok(config)
+    }
+
+    fn read_file(&mut self, path: &str) -> Result<(), ProxygenError> {
+        let mut text = String::new();
+        let mut file = try!(File::open(path));
+        try!(file.read_to_string(&mut text));
+
+        let mut parser = Parser::new(&text);
+        let table = match parser.parse() {
+            Some(v) => v,
+            None => {
+                let messages: Vec<String> = parser.errors
+                    .iter()
+                    .map(|e| e.desc.clone())
+                    .collect();
+                return Err(ProxygenError::ConfigError(format!("{}: {}",
+                                                              path,
+                                                              messages.join(", "))));
+            }
+        };
+
+        for (key, value) in table {
+            let value = match value {
+                Value::String(s) => s,
+                Value::Integer(i) => i.to_string(),
+                other => {
+                    return Err(ProxygenError::ConfigError(format!("{}: unsupported value for {}: \
+                                                                   {}",
+                                                                  path,
+                                                                  key,
+                                                                  other)))
+                }
+            };
+            try!(self.set(&key, &value));
+        }
+        Ok(())
+    }
+
+    fn set(&mut self, key: &str, value: &str) -> Result<(), ProxygenError> {
+        match key {
+            "address" => self.address = String::from(value),
+            "decks_dir" => self.decks_dir = String::from(value),
+            "card_index" => self.card_index = String::from(value),
+            "port" => self.port = try!(parse_number(key, value)),
+            "max_cards" => self.max_cards = try!(parse_number(key, value)),
+            "max_body_size" => self.max_body_size = try!(parse_number(key, value)),
+            "default_theme" => {
+                self.default_theme = match Theme::from_param(value) {
+                    Some(v) => v,
+                    None => {
+                        return Err(ProxygenError::ConfigError(format!("unknown theme {:?}", value)))
+                    }
+                }
+            }
+            _ => return Err(ProxygenError::ConfigError(format!("unknown setting {:?}", key))),
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,168 @@
+use std::sync::Arc;
+
+use super::regex::Regex;
+
+use super::card::Card;
+use super::error::ProxygenError;
+use super::i18n::CardLanguage;
+
+lazy_static!{
+    static ref BASE_RE: Regex = Regex::new(r"(\d+)?x?\s*(\D*?)\s*$").unwrap();
+    static ref SPLIT_RE: Regex = Regex::new(r"(.+?)\s*/+\s*.+").unwrap();
+    static ref HEADER_RE: Regex = Regex::new(concat!(
+        r"(?i)^(main ?deck|main|deck|side ?board|commander|companion|maybe ?board)",
+        r"\s*:?\s*(\(\d+\))?$")).unwrap();
+    static ref SIDEBOARD_RE: Regex = Regex::new(r"(?i)^SB:\s*(.*)$").unwrap();
+}
+
+/// Name of the section that lines marked "SB:" go in.
+const SIDEBOARD: &'static str = "Sideboard";
+
+/// Which part of a deck a section holds, for checking it against a format.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Part {
+    Main,
+    Sideboard,
+    /// Cards being considered, which aren't part of the deck at all.
+    Maybeboard,
+}
+
+/// A run of decklist lines under one heading. Cards before the first
+/// heading are in a section with an empty name.
+#[derive(Debug)]
+pub struct Section {
+    pub name: String,
+    pub cards: Vec<(u64, Arc<Card>)>,
+}
+
+impl Section {
+    /// Sections that aren't a sideboard or maybeboard, like "Commander" or
+    /// "// Creatures", are part of the main deck. A companion is kept
+    /// outside the game, like the sideboard.
+    pub fn part(&self) -> Part {
+        let name: String = self.name.to_lowercase().split_whitespace().collect();
+        match name.as_str() {
+            "sideboard" | "companion" => Part::Sideboard,
+            "maybeboard" => Part::Maybeboard,
+            _ => Part::Main,
+        }
+    }
+}
+
+/// The section a line starts, if it's a heading: a "//" comment, or a word
+/// like "Sideboard" or "Commander:" on its own.
+fn section_header(line: &str) -> Option<&str> {
+    if line.starts_with("//") {
+        return Some(line.trim_left_matches('/').trim());
+    }
+    HEADER_RE.captures(line).map(|captures| captures.at(1).unwrap())
+}
+
+/// Splits a line into its count, if it has one, and card name.
+pub fn split_line(line: &str) -> Option<(Option<&str>, &str)> {
+    BASE_RE.captures(line).map(|captures| (captures.at(1), captures.at(2).unwrap()))
+}
+
+/// The part of a multi-part name ("Fire // Ice", "Fire/Ice") to look up.
+pub fn front_name(name: &str) -> &str {
+    match SPLIT_RE.captures(name) {
+        Some(split_captures) => split_captures.at(1).unwrap(),
+        None => name,
+    }
+}
+
+/// Resolves every line of a decklist, keeping the sections its headings
+/// split it into. Lines marked "SB:" are gathered into a sideboard at the
+/// end. Lines that can't be resolved don't stop the others from being
+/// checked, so every bad line gets reported. Cards are drawn in `language`
+/// where the card data has it.
+pub fn parse_decklist(decklist: &str,
+                      max_cards: u64,
+                      language: CardLanguage)
+                      -> Result<Vec<Section>, ProxygenError> {
+    let mut count = 0;
+    let mut out = vec![Section {
+                           name: String::new(),
+                           cards: Vec::new(),
+                       }];
+    let mut sideboard = Vec::new();
+    let mut errors = Vec::new();
+    for (i, entry) in decklist.lines().enumerate() {
+        let line = i + 1;
+        let mut trimmed = entry.trim();
+        if let Some(name) = section_header(trimmed) {
+            if !name.is_empty() {
+                out.push(Section {
+                    name: String::from(name),
+                    cards: Vec::new(),
+                });
+            }
+            continue;
+        }
+        let in_sideboard = match SIDEBOARD_RE.captures(trimmed) {
+            Some(captures) => {
+                trimmed = captures.at(1).unwrap();
+                true
+            }
+            None => false,
+        };
+        if !trimmed.is_empty() {
+            let (n, c) = match split_line(trimmed) {
+                Some((amount, card_name)) => {
+                    let amount: u64 = match amount {
+                        Some(v) => {
+                            match v.parse() {
+                                Ok(v) => v,
+                                Err(_) => {
+                                    errors.push(ProxygenError::InvalidCount(String::from(v))
+                                        .on_line(line));
+                                    continue;
+                                }
+                            }
+                        }
+                        None => 1,
+                    };
+
+                    count = count.saturating_add(amount);
+                    if count > max_cards {
+                        return Err(ProxygenError::TooManyCards(max_cards).on_line(line));
+                    }
+
+                    let card = match Card::from_name_in(front_name(card_name), language) {
+                        Ok(v) => v,
+                        Err(e) => {
+                            errors.push(e.on_line(line));
+                            continue;
+                        }
+                    };
+
+                    (amount, card)
+                }
+                None => {
+                    errors.push(ProxygenError::DecklistParseError(String::from(trimmed))
+                        .on_line(line));
+                    continue;
+                }
+            };
+            if in_sideboard {
+                sideboard.push((n, c));
+            } else {
+                out.last_mut().unwrap().cards.push((n, c));
+            }
+        };
+    }
+
+    if !sideboard.is_empty() {
+        out.push(Section {
+            name: String::from(SIDEBOARD),
+            cards: sideboard,
+        });
+    }
+    out.retain(|section| !section.cards.is_empty());
+
+    match errors.len() {
+        0 => Ok(out),
+        1 => Err(errors.remove(0)),
+        _ => Err(ProxygenError::InvalidDecklist(errors)),
+    }
+}
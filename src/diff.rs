@@ -0,0 +1,71 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use super::card::Card;
+
+/// What changed between two resolved decklists.
+#[derive(Debug)]
+pub struct DeckDiff {
+    /// Cards to proxy, with how many more of each the new list has.
+    pub added: Vec<(u64, Arc<Card>)>,
+    /// Card names with how many fewer of each the new list has.
+    pub removed: Vec<(u64, String)>,
+}
+
+/// Adds up the counts of cards listed more than once, under any of their
+/// names, keeping the order in which each card first appears.
+fn merge_counts(cards: Vec<(u64, Arc<Card>)>) -> Vec<(u64, Arc<Card>)> {
+    let mut merged: Vec<(u64, Arc<Card>)> = Vec::new();
+    let mut positions: BTreeMap<String, usize> = BTreeMap::new();
+
+    for (n, card) in cards {
+        let key = String::from(card.key());
+        match positions.get(&key) {
+            Some(&i) => merged[i].0 += n,
+            None => {
+                positions.insert(key, merged.len());
+                merged.push((n, card));
+            }
+        }
+    }
+    merged
+}
+
+pub fn diff(old: Vec<(u64, Arc<Card>)>, new: Vec<(u64, Arc<Card>)>) -> DeckDiff {
+    let old = merge_counts(old);
+    let new = merge_counts(new);
+
+    let old_counts: BTreeMap<String, u64> = old.iter()
+        .map(|&(n, ref card)| (String::from(card.key()), n))
+        .collect();
+    let new_counts: BTreeMap<String, u64> = new.iter()
+        .map(|&(n, ref card)| (String::from(card.key()), n))
+        .collect();
+
+    let added = new.into_iter()
+        .filter_map(|(n, card)| {
+            let before = old_counts.get(card.key()).cloned().unwrap_or(0);
+            if n > before {
+                Some((n - before, card))
+            } else {
+                None
+            }
+        })
+        .collect();
+
+    let removed = old.into_iter()
+        .filter_map(|(n, card)| {
+            let after = new_counts.get(card.key()).cloned().unwrap_or(0);
+            if n > after {
+                Some((n - after, card.name()))
+            } else {
+                None
+            }
+        })
+        .collect();
+
+    DeckDiff {
+        added: added,
+        removed: removed,
+    }
+}
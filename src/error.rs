@@ -1,13 +1,247 @@
+use std::error::Error;
+use std::fmt;
+use std::io::Error as IoError;
+
 use super::serde_json::Error as JsonError;
 
+use super::i18n::{fill, Strings, ENGLISH};
+
 #[derive(Debug)]
 pub enum ProxygenError {
-    TooManyCards,
+    TooManyCards(u64),
     DecklistParseError(String),
+    InvalidCount(String),
     InvalidCardName(String),
+    /// A card search filter, like an oracle text pattern, couldn't be read.
+    InvalidSearch(String),
     MulticardHasNoNames(String),
     MulticardHasMalformedNames(String),
+    /// A face of a multi-part card is itself a multi-part card.
+    NestedMulticard(String),
+    /// The card database couldn't be loaded.
+    DatabaseError(String),
+    MissingField(String),
+    BodyTooLarge(u64),
+    LengthRequired,
+    PdfConversionFailed(String),
+    ConfigError(String),
+    InvalidPermalink(String),
+    InvalidDeckName(String),
+    DeckNotFound(String),
+    DeckAlreadyExists(String),
+    /// Several lines of a decklist had problems.
+    InvalidDecklist(Vec<ProxygenError>),
     JsonError(JsonError),
+    IoError(IoError),
+    /// Another error, caused by the given (1-based) line of a decklist.
+    OnLine {
+        line: usize,
+        error: Box<ProxygenError>,
+    },
+}
+
+/// The parts of an error that are shown to users, for error pages and as
+/// the body of JSON error responses.
+#[derive(Serialize, Debug)]
+pub struct ErrorReport {
+    pub code: &'static str,
+    pub message: String,
+    pub line: Option<usize>,
+    pub context: Option<String>,
+    /// The individual errors, for errors made up of several.
+    pub errors: Vec<ErrorReport>,
+}
+
+impl ProxygenError {
+    pub fn on_line(self, line: usize) -> ProxygenError {
+        ProxygenError::OnLine {
+            line: line,
+            error: Box::new(self),
+        }
+    }
+
+    /// Identifies the kind of error. These don't change between versions,
+    /// so clients can match on them.
+    pub fn code(&self) -> &'static str {
+        match *self {
+            ProxygenError::TooManyCards(_) => "too_many_cards",
+            ProxygenError::DecklistParseError(_) => "decklist_parse_error",
+            ProxygenError::InvalidCount(_) => "invalid_count",
+            ProxygenError::InvalidCardName(_) => "invalid_card_name",
+            ProxygenError::InvalidSearch(_) => "invalid_search",
+            ProxygenError::MulticardHasNoNames(_) => "multicard_has_no_names",
+            ProxygenError::MulticardHasMalformedNames(_) => "multicard_has_malformed_names",
+            ProxygenError::NestedMulticard(_) => "nested_multicard",
+            ProxygenError::DatabaseError(_) => "database_error",
+            ProxygenError::MissingField(_) => "missing_field",
+            ProxygenError::BodyTooLarge(_) => "body_too_large",
+            ProxygenError::LengthRequired => "length_required",
+            ProxygenError::PdfConversionFailed(_) => "pdf_conversion_failed",
+            ProxygenError::ConfigError(_) => "config_error",
+            ProxygenError::InvalidPermalink(_) => "invalid_permalink",
+            ProxygenError::InvalidDeckName(_) => "invalid_deck_name",
+            ProxygenError::DeckNotFound(_) => "deck_not_found",
+            ProxygenError::DeckAlreadyExists(_) => "deck_already_exists",
+            ProxygenError::InvalidDecklist(_) => "invalid_decklist",
+            ProxygenError::JsonError(_) => "json_error",
+            ProxygenError::IoError(_) => "io_error",
+            ProxygenError::OnLine { ref error, .. } => error.code(),
+        }
+    }
+
+    /// The decklist line the error happened on, if it came from one.
+    pub fn line(&self) -> Option<usize> {
+        match *self {
+            ProxygenError::OnLine { line, .. } => Some(line),
+            _ => None,
+        }
+    }
+
+    /// The input the error is about, like the card name that wasn't found.
+    pub fn context(&self) -> Option<&str> {
+        match *self {
+            ProxygenError::DecklistParseError(ref s) |
+            ProxygenError::InvalidCount(ref s) |
+            ProxygenError::InvalidCardName(ref s) |
+            ProxygenError::InvalidSearch(ref s) |
+            ProxygenError::MulticardHasNoNames(ref s) |
+            ProxygenError::MulticardHasMalformedNames(ref s) |
+            ProxygenError::NestedMulticard(ref s) |
+            ProxygenError::MissingField(ref s) |
+            ProxygenError::InvalidPermalink(ref s) |
+            ProxygenError::InvalidDeckName(ref s) |
+            ProxygenError::DeckNotFound(ref s) |
+            ProxygenError::DeckAlreadyExists(ref s) => Some(s),
+            ProxygenError::OnLine { ref error, .. } => error.context(),
+            _ => None,
+        }
+    }
+
+    /// Whether the error is the fault of the request rather than the server.
+    pub fn is_user_error(&self) -> bool {
+        match *self {
+            ProxygenError::TooManyCards(_) |
+            ProxygenError::DecklistParseError(_) |
+            ProxygenError::InvalidCount(_) |
+            ProxygenError::InvalidCardName(_) |
+            ProxygenError::InvalidSearch(_) |
+            ProxygenError::MissingField(_) |
+            ProxygenError::BodyTooLarge(_) |
+            ProxygenError::LengthRequired |
+            ProxygenError::InvalidPermalink(_) |
+            ProxygenError::InvalidDeckName(_) |
+            ProxygenError::DeckNotFound(_) |
+            ProxygenError::DeckAlreadyExists(_) => true,
+            ProxygenError::InvalidDecklist(ref errors) => errors.iter().all(|e| e.is_user_error()),
+            ProxygenError::OnLine { ref error, .. } => error.is_user_error(),
+            _ => false,
+        }
+    }
+
+    /// The individual errors this one is made of.
+    pub fn parts(&self) -> Vec<&ProxygenError> {
+        match *self {
+            ProxygenError::InvalidDecklist(ref errors) => errors.iter().collect(),
+            _ => vec![self],
+        }
+    }
+
+    /// The message shown to users, in the language of `strings`. Errors
+    /// that are the server's problem are always in English.
+    pub fn message(&self, strings: &Strings) -> String {
+        match *self {
+            ProxygenError::TooManyCards(max) => fill(strings.too_many_cards, &[&max.to_string()]),
+            ProxygenError::DecklistParseError(ref s) => fill(strings.decklist_parse_error, &[s]),
+            ProxygenError::InvalidCount(ref s) => fill(strings.invalid_count, &[s]),
+            ProxygenError::InvalidCardName(ref s) => fill(strings.invalid_card_name, &[s]),
+            ProxygenError::InvalidSearch(ref s) => fill(strings.invalid_search, &[s]),
+            ProxygenError::MulticardHasNoNames(ref s) |
+            ProxygenError::MulticardHasMalformedNames(ref s) => {
+                fill(strings.multicard_missing_half, &[s])
+            }
+            ProxygenError::NestedMulticard(ref s) => fill(strings.nested_multicard, &[s]),
+            ProxygenError::MissingField(ref s) => fill(strings.missing_field, &[s]),
+            ProxygenError::BodyTooLarge(max) => fill(strings.body_too_large, &[&max.to_string()]),
+            ProxygenError::LengthRequired => String::from(strings.length_required),
+            ProxygenError::InvalidPermalink(_) => String::from(strings.invalid_permalink),
+            ProxygenError::InvalidDeckName(ref s) => fill(strings.invalid_deck_name, &[s]),
+            ProxygenError::DeckNotFound(ref s) => fill(strings.deck_not_found, &[s]),
+            ProxygenError::DeckAlreadyExists(ref s) => fill(strings.deck_already_exists, &[s]),
+            ProxygenError::InvalidDecklist(ref errors) => {
+                fill(strings.invalid_decklist, &[&errors.len().to_string()])
+            }
+            ProxygenError::OnLine { line, ref error } => {
+                fill(strings.on_line, &[&line.to_string(), &error.message(strings)])
+            }
+            _ => self.to_string(),
+        }
+    }
+
+    pub fn report(&self) -> ErrorReport {
+        self.report_in(&ENGLISH)
+    }
+
+    /// Like `report`, with the messages in the language of `strings`.
+    pub fn report_in(&self, strings: &Strings) -> ErrorReport {
+        ErrorReport {
+            code: self.code(),
+            message: self.message(strings),
+            line: self.line(),
+            context: self.context().map(String::from),
+            errors: match *self {
+                ProxygenError::InvalidDecklist(ref errors) => {
+                    errors.iter().map(|e| e.report_in(strings)).collect()
+                }
+                _ => Vec::new(),
+            },
+        }
+    }
+}
+
+impl fmt::Display for ProxygenError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ProxygenError::PdfConversionFailed(ref s) => {
+                write!(f, "Could not convert the sheet to PDF: {}", s)
+            }
+            ProxygenError::ConfigError(ref s) => write!(f, "Invalid configuration: {}", s),
+            ProxygenError::DatabaseError(ref s) => {
+                write!(f, "The card database could not be loaded: {}", s)
+            }
+            ProxygenError::JsonError(ref e) => write!(f, "Could not read JSON: {}", e),
+            ProxygenError::IoError(ref e) => write!(f, "{}", e),
+            // Each line's problem on a line of its own, since plain text
+            // has nowhere else to show them.
+            ProxygenError::InvalidDecklist(ref errors) => {
+                try!(f.write_str(&self.message(&ENGLISH)));
+                for error in errors {
+                    try!(write!(f, "\n  {}", error));
+                }
+                Ok(())
+            }
+            _ => f.write_str(&self.message(&ENGLISH)),
+        }
+    }
+}
+
+impl Error for ProxygenError {
+    fn description(&self) -> &str {
+        match *self {
+            ProxygenError::JsonError(ref e) => e.description(),
+            ProxygenError::IoError(ref e) => e.description(),
+            ProxygenError::OnLine { ref error, .. } => error.description(),
+            _ => self.code(),
+        }
+    }
+
+    fn cause(&self) -> Option<&Error> {
+        match *self {
+            ProxygenError::JsonError(ref e) => Some(e),
+            ProxygenError::IoError(ref e) => Some(e),
+            ProxygenError::OnLine { ref error, .. } => Some(&**error),
+            _ => None,
+        }
+    }
 }
 
 impl From<JsonError> for ProxygenError {
@@ -15,3 +249,9 @@ impl From<JsonError> for ProxygenError {
         ProxygenError::JsonError(e)
     }
 }
+
+impl From<IoError> for ProxygenError {
+    fn from(e: IoError) -> ProxygenError {
+        ProxygenError::IoError(e)
+    }
+}
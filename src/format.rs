@@ -0,0 +1,122 @@
+use std::collections::BTreeMap;
+
+use super::card::Card;
+use super::decklist::{Part, Section};
+use super::i18n::{fill, Strings};
+use super::render::Report;
+
+/// Constructed formats a deck can be checked against.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Standard,
+    Modern,
+    Legacy,
+    Vintage,
+    Commander,
+}
+
+pub const FORMATS: [Format; 5] = [Format::Standard,
+                                  Format::Modern,
+                                  Format::Legacy,
+                                  Format::Vintage,
+                                  Format::Commander];
+
+impl Format {
+    pub fn from_param(param: &str) -> Option<Format> {
+        FORMATS.iter().find(|format| format.param() == param).cloned()
+    }
+
+    /// Value used for the format in forms and query strings.
+    pub fn param(&self) -> &'static str {
+        match *self {
+            Format::Standard => "standard",
+            Format::Modern => "modern",
+            Format::Legacy => "legacy",
+            Format::Vintage => "vintage",
+            Format::Commander => "commander",
+        }
+    }
+
+    /// The format's name, as the card data's legalities give it.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Format::Standard => "Standard",
+            Format::Modern => "Modern",
+            Format::Legacy => "Legacy",
+            Format::Vintage => "Vintage",
+            Format::Commander => "Commander",
+        }
+    }
+
+    fn copy_limit(&self) -> u64 {
+        match *self {
+            Format::Commander => 1,
+            _ => 4,
+        }
+    }
+}
+
+/// Everything about a deck that breaks the format's rules. The size is
+/// the main deck's, while copies are counted across the main deck and the
+/// sideboard. Maybeboards aren't checked.
+pub fn problems(format: Format, sections: &[Section], strings: &Strings) -> Vec<String> {
+    let deck = || sections.iter().filter(|section| section.part() != Part::Maybeboard);
+
+    // By key, so copies listed under different names or in different
+    // languages add up.
+    let mut counts: BTreeMap<&str, (u64, &Card)> = BTreeMap::new();
+    for &(n, ref card) in deck().flat_map(|section| section.cards.iter()) {
+        counts.entry(card.key()).or_insert((0, &**card)).0 += n;
+    }
+
+    let mut problems = Vec::new();
+
+    let size: u64 = deck()
+        .filter(|section| section.part() == Part::Main)
+        .flat_map(|section| section.cards.iter())
+        .map(|&(n, _)| n)
+        .sum();
+    match format {
+        Format::Commander if size != 100 => {
+            problems.push(fill(strings.commander_size, &[&size.to_string()]))
+        }
+        Format::Commander => {}
+        _ if size < 60 => problems.push(fill(strings.deck_too_small, &[&size.to_string(), "60"])),
+        _ => {}
+    }
+
+    for &(n, card) in counts.values() {
+        let name = card.name();
+        let limit = match card.legality(format.name()) {
+            Some("Banned") => {
+                problems.push(fill(strings.banned, &[&name]));
+                continue;
+            }
+            Some("Restricted") => 1,
+            Some(_) => format.copy_limit(),
+            None => {
+                problems.push(fill(strings.not_legal, &[&name]));
+                continue;
+            }
+        };
+        if n > limit && !card.unlimited_copies() {
+            problems.push(fill(strings.too_many_copies,
+                               &[&n.to_string(), &name, &limit.to_string()]));
+        }
+    }
+    problems
+}
+
+/// The result of checking a deck against a format, to show above the
+/// proxies.
+pub fn report(format: Format, sections: &[Section], strings: &Strings) -> Report {
+    let problems = problems(format, sections, strings);
+    Report {
+        title: fill(strings.format_report, &[format.name()]),
+        lines: if problems.is_empty() {
+            vec![fill(strings.format_legal, &[format.name()])]
+        } else {
+            problems
+        },
+    }
+}
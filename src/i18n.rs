@@ -0,0 +1,555 @@
+/// Languages the pages can be shown in. Cards can be printed in these too,
+/// where the card data has translations.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Language {
+    English,
+    German,
+    Spanish,
+    Japanese,
+}
+
+pub const LANGUAGES: [Language; 4] = [Language::English,
+                                      Language::German,
+                                      Language::Spanish,
+                                      Language::Japanese];
+
+impl Default for Language {
+    fn default() -> Language {
+        Language::English
+    }
+}
+
+impl Language {
+    pub fn from_param(param: &str) -> Option<Language> {
+        LANGUAGES.iter().find(|language| language.param() == param).cloned()
+    }
+
+    /// Value used for the language in forms and query strings.
+    pub fn param(&self) -> &'static str {
+        match *self {
+            Language::English => "en",
+            Language::German => "de",
+            Language::Spanish => "es",
+            Language::Japanese => "ja",
+        }
+    }
+
+    /// The language's name for itself, for language pickers.
+    pub fn label(&self) -> &'static str {
+        match *self {
+            Language::English => "English",
+            Language::German => "Deutsch",
+            Language::Spanish => "Español",
+            Language::Japanese => "日本語",
+        }
+    }
+
+    /// The name the card data uses for the language.
+    pub fn card_data_name(&self) -> &'static str {
+        match *self {
+            Language::English => "English",
+            Language::German => "German",
+            Language::Spanish => "Spanish",
+            Language::Japanese => "Japanese",
+        }
+    }
+
+    pub fn strings(&self) -> &'static Strings {
+        match *self {
+            Language::English => &ENGLISH,
+            Language::German => &GERMAN,
+            Language::Spanish => &SPANISH,
+            Language::Japanese => &JAPANESE,
+        }
+    }
+}
+
+/// Which language cards are printed in.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CardLanguage {
+    /// Cards listed by a translated name are printed in that language, the
+    /// rest in English.
+    AsListed,
+    In(Language),
+}
+
+impl Default for CardLanguage {
+    fn default() -> CardLanguage {
+        CardLanguage::In(Language::English)
+    }
+}
+
+impl CardLanguage {
+    pub fn from_param(param: &str) -> Option<CardLanguage> {
+        match param {
+            "as_listed" => Some(CardLanguage::AsListed),
+            _ => Language::from_param(param).map(CardLanguage::In),
+        }
+    }
+
+    /// Value used for the card language in forms and query strings.
+    pub fn param(&self) -> &'static str {
+        match *self {
+            CardLanguage::AsListed => "as_listed",
+            CardLanguage::In(language) => language.param(),
+        }
+    }
+}
+
+/// Replaces each "{}" in a template with the next argument.
+pub fn fill(template: &str, args: &[&str]) -> String {
+    let mut s = String::new();
+    for (i, part) in template.split("{}").enumerate() {
+        if i > 0 {
+            s.push_str(args.get(i - 1).cloned().unwrap_or(""));
+        }
+        s.push_str(part);
+    }
+    s
+}
+
+/// Every piece of text the pages show, in one language. Templates have a
+/// "{}" for each value filled in with `fill`.
+pub struct Strings {
+    pub heading: &'static str,
+    pub theme: &'static str,
+    /// Labels for the themes, in the order of `THEMES`.
+    pub themes: [&'static str; 4],
+    pub color_frames: &'static str,
+    pub page_language: &'static str,
+    pub card_language: &'static str,
+    pub as_listed: &'static str,
+    pub skip_owned: &'static str,
+    pub skip_owned_help: &'static str,
+    pub submit: &'static str,
+    pub deck_name: &'static str,
+    pub save_deck: &'static str,
+    pub saved_decks: &'static str,
+    pub print: &'static str,
+    pub edit: &'static str,
+    pub rename: &'static str,
+    pub delete: &'static str,
+    pub diff_link: &'static str,
+    pub report_errors: &'static str,
+    pub project_page: &'static str,
+    pub report_errors_end: &'static str,
+    pub diff_heading: &'static str,
+    pub old_list: &'static str,
+    pub new_list: &'static str,
+    pub back: &'static str,
+    pub sheet_link: &'static str,
+    pub edit_link: &'static str,
+    pub removed: &'static str,
+    pub nothing_removed: &'static str,
+    pub skipped: &'static str,
+    pub nothing_skipped: &'static str,
+    pub unimplemented: &'static str,
+    pub complain: &'static str,
+    pub format: &'static str,
+    pub no_format: &'static str,
+    pub format_report: &'static str,
+    pub format_legal: &'static str,
+    pub deck_too_small: &'static str,
+    pub commander_size: &'static str,
+    pub banned: &'static str,
+    pub not_legal: &'static str,
+    pub too_many_copies: &'static str,
+    pub stats: &'static str,
+    pub stats_heading: &'static str,
+    pub total_cards: &'static str,
+    pub lands: &'static str,
+    pub average_mana_value: &'static str,
+    pub mana_curve: &'static str,
+    pub colors: &'static str,
+    /// White, blue, black, red, green and colorless.
+    pub color_names: [&'static str; 6],
+    pub card_types: &'static str,
+    /// Creature, planeswalker, instant, sorcery, enchantment, artifact and
+    /// land.
+    pub type_names: [&'static str; 7],
+    pub order: &'static str,
+    /// As entered, by name, by mana value, by color, by type and by
+    /// section.
+    pub orders: [&'static str; 6],
+    pub search: &'static str,
+    pub search_name: &'static str,
+    pub search_type: &'static str,
+    pub search_oracle: &'static str,
+    pub search_mana_value: &'static str,
+    pub search_colors: &'static str,
+    pub search_button: &'static str,
+    pub add_card: &'static str,
+    pub no_results: &'static str,
+
+    pub too_many_cards: &'static str,
+    pub decklist_parse_error: &'static str,
+    pub invalid_count: &'static str,
+    pub invalid_card_name: &'static str,
+    pub invalid_search: &'static str,
+    pub multicard_missing_half: &'static str,
+    pub nested_multicard: &'static str,
+    pub missing_field: &'static str,
+    pub body_too_large: &'static str,
+    pub length_required: &'static str,
+    pub invalid_permalink: &'static str,
+    pub invalid_deck_name: &'static str,
+    pub deck_not_found: &'static str,
+    pub deck_already_exists: &'static str,
+    pub invalid_decklist: &'static str,
+    pub on_line: &'static str,
+}
+
+pub static ENGLISH: Strings = Strings {
+    heading: "Simple Proxy Generator",
+    theme: "Theme",
+    themes: ["Standard", "Minimal ink", "High contrast, large print", "Classic frame"],
+    color_frames: "Color-tinted frames",
+    page_language: "Page language",
+    card_language: "Print cards in",
+    as_listed: "The language they're listed in",
+    skip_owned: "Skip cards I own",
+    skip_owned_help: "Paste your collection as a list or a CSV export. Cards in it are left out \
+                      of the proxies.",
+    submit: "Make proxies",
+    deck_name: "Deck name",
+    save_deck: "Save deck",
+    saved_decks: "Saved decks",
+    print: "Print",
+    edit: "Edit",
+    rename: "Rename",
+    delete: "Delete",
+    diff_link: "Only proxy what changed between two lists",
+    report_errors: "Please report any errors to the issue tracker on my ",
+    project_page: "Github project page",
+    report_errors_end: ".",
+    diff_heading: "Proxy Changes",
+    old_list: "Old list",
+    new_list: "New list",
+    back: "Back to the proxy generator",
+    sheet_link: "Link to this sheet",
+    edit_link: "Edit this list",
+    removed: "Removed",
+    nothing_removed: "Nothing was removed.",
+    skipped: "Skipped, already owned",
+    nothing_skipped: "None of these cards are in the collection.",
+    unimplemented: "This type of card ({}) is not yet implemented.",
+    complain: "Go complain to the developer",
+    format: "Check legality in",
+    no_format: "No format",
+    format_report: "{} legality",
+    format_legal: "The deck is legal in {}.",
+    deck_too_small: "The deck has {} cards, it needs at least {}",
+    commander_size: "Commander decks have exactly 100 cards, this one has {}",
+    banned: "{} is banned",
+    not_legal: "{} is not legal in this format",
+    too_many_copies: "{} copies of {}, at most {} are allowed",
+    stats: "Start with a page of deck statistics",
+    stats_heading: "Deck statistics",
+    total_cards: "Cards",
+    lands: "Lands",
+    average_mana_value: "Average mana value (without lands)",
+    mana_curve: "Mana curve",
+    colors: "Colors",
+    color_names: ["White", "Blue", "Black", "Red", "Green", "Colorless"],
+    card_types: "Card types",
+    type_names: ["Creature", "Planeswalker", "Instant", "Sorcery", "Enchantment", "Artifact",
+                 "Land"],
+    order: "Print cards",
+    orders: ["as entered", "by name", "by mana value", "by color", "by type", "by section"],
+    search: "Search for cards",
+    search_name: "Name contains",
+    search_type: "Type line contains",
+    search_oracle: "Text matches (regular expression)",
+    search_mana_value: "Mana value",
+    search_colors: "Colors",
+    search_button: "Search",
+    add_card: "Add",
+    no_results: "No cards found",
+
+    too_many_cards: "Too many proxies requested. Request at most {} proxies at a time",
+    decklist_parse_error: "Could not read line \"{}\"",
+    invalid_count: "\"{}\" is not a card count this can print",
+    invalid_card_name: "No card is named \"{}\"",
+    invalid_search: "Can't search for \"{}\"",
+    multicard_missing_half: "The split/flip/transform/meld card \"{}\" is missing its other half \
+                             in the card database",
+    nested_multicard: "The card \"{}\" has a layout that can't be rendered",
+    missing_field: "The form is missing \"{}\"",
+    body_too_large: "The request is too large. Send at most {} bytes",
+    length_required: "The request must have a Content-Length",
+    invalid_permalink: "The link is invalid or corrupted",
+    invalid_deck_name: "\"{}\" can't be used as a deck name. Use at least one letter or number",
+    deck_not_found: "There is no saved deck \"{}\"",
+    deck_already_exists: "A deck named \"{}\" already exists",
+    invalid_decklist: "{} lines of the decklist have problems",
+    on_line: "Line {}: {}",
+};
+
+pub static GERMAN: Strings = Strings {
+    heading: "Einfacher Proxy-Generator",
+    theme: "Design",
+    themes: ["Standard", "Wenig Tinte", "Hoher Kontrast, große Schrift", "Klassischer Rahmen"],
+    color_frames: "Farbige Rahmen",
+    page_language: "Sprache der Seite",
+    card_language: "Karten drucken auf",
+    as_listed: "Der Sprache, in der sie aufgelistet sind",
+    skip_owned: "Karten überspringen, die ich besitze",
+    skip_owned_help: "Füge deine Sammlung als Liste oder CSV-Export ein. Karten daraus werden \
+                      nicht als Proxy gedruckt.",
+    submit: "Proxies erstellen",
+    deck_name: "Deckname",
+    save_deck: "Deck speichern",
+    saved_decks: "Gespeicherte Decks",
+    print: "Drucken",
+    edit: "Bearbeiten",
+    rename: "Umbenennen",
+    delete: "Löschen",
+    diff_link: "Nur Änderungen zwischen zwei Listen drucken",
+    report_errors: "Bitte melde Fehler im Issue-Tracker auf meiner ",
+    project_page: "Github-Projektseite",
+    report_errors_end: ".",
+    diff_heading: "Änderungen drucken",
+    old_list: "Alte Liste",
+    new_list: "Neue Liste",
+    back: "Zurück zum Proxy-Generator",
+    sheet_link: "Link zu diesem Bogen",
+    edit_link: "Diese Liste bearbeiten",
+    removed: "Entfernt",
+    nothing_removed: "Es wurde nichts entfernt.",
+    skipped: "Übersprungen, schon vorhanden",
+    nothing_skipped: "Keine dieser Karten ist in der Sammlung.",
+    unimplemented: "Diese Kartenart ({}) wird noch nicht unterstützt.",
+    complain: "Beschwer dich beim Entwickler",
+    format: "Legalität prüfen für",
+    no_format: "Kein Format",
+    format_report: "Legalität in {}",
+    format_legal: "Das Deck ist in {} legal.",
+    deck_too_small: "Das Deck hat {} Karten, es braucht mindestens {}",
+    commander_size: "Commander-Decks haben genau 100 Karten, dieses hat {}",
+    banned: "{} ist gebannt",
+    not_legal: "{} ist in diesem Format nicht legal",
+    too_many_copies: "{} Exemplare von {}, höchstens {} sind erlaubt",
+    stats: "Mit einer Seite Deckstatistik beginnen",
+    stats_heading: "Deckstatistik",
+    total_cards: "Karten",
+    lands: "Länder",
+    average_mana_value: "Durchschnittlicher Manawert (ohne Länder)",
+    mana_curve: "Manakurve",
+    colors: "Farben",
+    color_names: ["Weiß", "Blau", "Schwarz", "Rot", "Grün", "Farblos"],
+    card_types: "Kartentypen",
+    type_names: ["Kreatur", "Planeswalker", "Spontanzauber", "Hexerei", "Verzauberung",
+                 "Artefakt", "Land"],
+    order: "Karten drucken",
+    orders: ["wie eingegeben",
+             "nach Name",
+             "nach Manabetrag",
+             "nach Farbe",
+             "nach Kartentyp",
+             "nach Abschnitt"],
+    search: "Karten suchen",
+    search_name: "Name enthält",
+    search_type: "Typzeile enthält",
+    search_oracle: "Text passt auf (regulärer Ausdruck)",
+    search_mana_value: "Manabetrag",
+    search_colors: "Farben",
+    search_button: "Suchen",
+    add_card: "Hinzufügen",
+    no_results: "Keine Karten gefunden",
+
+    too_many_cards: "Zu viele Proxies angefordert. Fordere höchstens {} Proxies auf einmal an",
+    decklist_parse_error: "Zeile \"{}\" konnte nicht gelesen werden",
+    invalid_count: "\"{}\" ist keine Kartenanzahl, die gedruckt werden kann",
+    invalid_card_name: "Keine Karte heißt \"{}\"",
+    invalid_search: "Nach \"{}\" kann nicht gesucht werden",
+    multicard_missing_half: "Der Split-/Flip-/Transform-/Meld-Karte \"{}\" fehlt in der \
+                             Kartendatenbank ihre andere Hälfte",
+    nested_multicard: "Die Karte \"{}\" hat ein Layout, das nicht dargestellt werden kann",
+    missing_field: "Im Formular fehlt \"{}\"",
+    body_too_large: "Die Anfrage ist zu groß. Sende höchstens {} Bytes",
+    length_required: "Die Anfrage braucht eine Content-Length",
+    invalid_permalink: "Der Link ist ungültig oder beschädigt",
+    invalid_deck_name: "\"{}\" kann nicht als Deckname verwendet werden. Verwende mindestens einen \
+                        Buchstaben oder eine Ziffer",
+    deck_not_found: "Es gibt kein gespeichertes Deck \"{}\"",
+    deck_already_exists: "Ein Deck namens \"{}\" existiert bereits",
+    invalid_decklist: "{} Zeilen der Deckliste haben Probleme",
+    on_line: "Zeile {}: {}",
+};
+
+pub static SPANISH: Strings = Strings {
+    heading: "Generador de proxies sencillo",
+    theme: "Tema",
+    themes: ["Estándar", "Poca tinta", "Alto contraste, letra grande", "Marco clásico"],
+    color_frames: "Marcos teñidos por color",
+    page_language: "Idioma de la página",
+    card_language: "Imprimir cartas en",
+    as_listed: "El idioma en que están en la lista",
+    skip_owned: "Omitir cartas que ya tengo",
+    skip_owned_help: "Pega tu colección como lista o exportación CSV. Las cartas que contiene no \
+                      se imprimen.",
+    submit: "Crear proxies",
+    deck_name: "Nombre del mazo",
+    save_deck: "Guardar mazo",
+    saved_decks: "Mazos guardados",
+    print: "Imprimir",
+    edit: "Editar",
+    rename: "Renombrar",
+    delete: "Borrar",
+    diff_link: "Imprimir solo lo que cambió entre dos listas",
+    report_errors: "Por favor, informa de cualquier error en el gestor de incidencias de mi ",
+    project_page: "página del proyecto en Github",
+    report_errors_end: ".",
+    diff_heading: "Imprimir cambios",
+    old_list: "Lista anterior",
+    new_list: "Lista nueva",
+    back: "Volver al generador de proxies",
+    sheet_link: "Enlace a esta hoja",
+    edit_link: "Editar esta lista",
+    removed: "Quitadas",
+    nothing_removed: "No se quitó nada.",
+    skipped: "Omitidas, ya las tienes",
+    nothing_skipped: "Ninguna de estas cartas está en la colección.",
+    unimplemented: "Este tipo de carta ({}) todavía no está implementado.",
+    complain: "Quéjate al desarrollador",
+    format: "Comprobar legalidad en",
+    no_format: "Ningún formato",
+    format_report: "Legalidad en {}",
+    format_legal: "El mazo es legal en {}.",
+    deck_too_small: "El mazo tiene {} cartas, necesita al menos {}",
+    commander_size: "Los mazos de Commander tienen exactamente 100 cartas, este tiene {}",
+    banned: "{} está prohibida",
+    not_legal: "{} no es legal en este formato",
+    too_many_copies: "{} copias de {}, se permiten como máximo {}",
+    stats: "Empezar con una página de estadísticas del mazo",
+    stats_heading: "Estadísticas del mazo",
+    total_cards: "Cartas",
+    lands: "Tierras",
+    average_mana_value: "Valor de maná medio (sin tierras)",
+    mana_curve: "Curva de maná",
+    colors: "Colores",
+    color_names: ["Blanco", "Azul", "Negro", "Rojo", "Verde", "Incoloro"],
+    card_types: "Tipos de carta",
+    type_names: ["Criatura", "Planeswalker", "Instantáneo", "Conjuro", "Encantamiento",
+                 "Artefacto", "Tierra"],
+    order: "Imprimir cartas",
+    orders: ["como se introdujeron",
+             "por nombre",
+             "por valor de maná",
+             "por color",
+             "por tipo",
+             "por sección"],
+    search: "Buscar cartas",
+    search_name: "El nombre contiene",
+    search_type: "La línea de tipo contiene",
+    search_oracle: "El texto coincide con (expresión regular)",
+    search_mana_value: "Valor de maná",
+    search_colors: "Colores",
+    search_button: "Buscar",
+    add_card: "Añadir",
+    no_results: "No se encontraron cartas",
+
+    too_many_cards: "Demasiados proxies. Pide como máximo {} proxies a la vez",
+    decklist_parse_error: "No se pudo leer la línea \"{}\"",
+    invalid_count: "\"{}\" no es una cantidad de cartas que se pueda imprimir",
+    invalid_card_name: "Ninguna carta se llama \"{}\"",
+    invalid_search: "No se puede buscar \"{}\"",
+    multicard_missing_half: "A la carta dividida/girada/transformable/fusionable \"{}\" le falta \
+                             su otra mitad en la base de datos",
+    nested_multicard: "La carta \"{}\" tiene un formato que no se puede mostrar",
+    missing_field: "Al formulario le falta \"{}\"",
+    body_too_large: "La petición es demasiado grande. Envía como máximo {} bytes",
+    length_required: "La petición debe tener Content-Length",
+    invalid_permalink: "El enlace no es válido o está dañado",
+    invalid_deck_name: "\"{}\" no se puede usar como nombre de mazo. Usa al menos una letra o un \
+                        número",
+    deck_not_found: "No hay ningún mazo guardado \"{}\"",
+    deck_already_exists: "Ya existe un mazo llamado \"{}\"",
+    invalid_decklist: "{} líneas de la lista tienen problemas",
+    on_line: "Línea {}: {}",
+};
+
+pub static JAPANESE: Strings = Strings {
+    heading: "シンプルなプロキシ作成ツール",
+    theme: "テーマ",
+    themes: ["標準", "インク節約", "高コントラスト・大きな文字", "クラシック枠"],
+    color_frames: "色付きの枠",
+    page_language: "ページの言語",
+    card_language: "カードの言語",
+    as_listed: "リストに書かれた言語",
+    skip_owned: "持っているカードを除く",
+    skip_owned_help: "コレクションをリストかCSVで貼り付けてください。含まれるカードはプロキシに\
+                      しません。",
+    submit: "プロキシを作成",
+    deck_name: "デッキ名",
+    save_deck: "デッキを保存",
+    saved_decks: "保存したデッキ",
+    print: "印刷",
+    edit: "編集",
+    rename: "名前を変更",
+    delete: "削除",
+    diff_link: "2つのリストの差分だけをプロキシにする",
+    report_errors: "不具合は次のページの課題管理で報告してください: ",
+    project_page: "Githubのプロジェクトページ",
+    report_errors_end: "。",
+    diff_heading: "差分のプロキシ",
+    old_list: "古いリスト",
+    new_list: "新しいリスト",
+    back: "プロキシ作成ツールに戻る",
+    sheet_link: "このシートへのリンク",
+    edit_link: "このリストを編集",
+    removed: "削除されたカード",
+    nothing_removed: "削除されたカードはありません。",
+    skipped: "所持済みのため除外",
+    nothing_skipped: "コレクションにあるカードはありません。",
+    unimplemented: "この種類のカード ({}) はまだ対応していません。",
+    complain: "開発者に文句を言ってください",
+    format: "適正をチェックするフォーマット",
+    no_format: "なし",
+    format_report: "{}での適正",
+    format_legal: "このデッキは{}で使用できます。",
+    deck_too_small: "デッキは{}枚です。{}枚以上必要です",
+    commander_size: "統率者戦のデッキはちょうど100枚です。このデッキは{}枚です",
+    banned: "{}は禁止カードです",
+    not_legal: "{}はこのフォーマットでは使用できません",
+    too_many_copies: "{}枚の{}があります。{}枚までです",
+    stats: "最初にデッキの統計ページを付ける",
+    stats_heading: "デッキの統計",
+    total_cards: "カード",
+    lands: "土地",
+    average_mana_value: "平均マナ総量 (土地を除く)",
+    mana_curve: "マナカーブ",
+    colors: "色",
+    color_names: ["白", "青", "黒", "赤", "緑", "無色"],
+    card_types: "カード・タイプ",
+    type_names: ["クリーチャー", "プレインズウォーカー", "インスタント", "ソーサリー",
+                 "エンチャント", "アーティファクト", "土地"],
+    order: "カードの並び順",
+    orders: ["入力順", "名前順", "マナ総量順", "色順", "カード・タイプ順", "セクション順"],
+    search: "カードを検索",
+    search_name: "名前に含む",
+    search_type: "タイプ行に含む",
+    search_oracle: "テキストが一致（正規表現）",
+    search_mana_value: "マナ総量",
+    search_colors: "色",
+    search_button: "検索",
+    add_card: "追加",
+    no_results: "カードが見つかりません",
+
+    too_many_cards: "プロキシが多すぎます。一度に{}枚までにしてください",
+    decklist_parse_error: "「{}」の行を読み取れません",
+    invalid_count: "「{}」は印刷できる枚数ではありません",
+    invalid_card_name: "「{}」という名前のカードはありません",
+    invalid_search: "「{}」は検索できません",
+    multicard_missing_half: "分割・反転・両面・合体カード「{}」のもう片方がカードデータにありません",
+    nested_multicard: "カード「{}」のレイアウトは表示できません",
+    missing_field: "フォームに「{}」がありません",
+    body_too_large: "リクエストが大きすぎます。{}バイト以下にしてください",
+    length_required: "リクエストにはContent-Lengthが必要です",
+    invalid_permalink: "リンクが無効か壊れています",
+    invalid_deck_name: "「{}」はデッキ名に使えません。文字か数字を1つ以上使ってください",
+    deck_not_found: "保存されたデッキ「{}」はありません",
+    deck_already_exists: "「{}」という名前のデッキはすでにあります",
+    invalid_decklist: "デッキリストの{}行に問題があります",
+    on_line: "{}行目: {}",
+};
@@ -9,207 +9,179 @@
 
 extern crate serde;
 extern crate serde_json;
+extern crate bincode;
 
 extern crate maud;
-use maud::PreEscaped;
 
 #[macro_use]
 extern crate nickel;
-use nickel::{Nickel, HttpRouter, FormBody};
-use nickel::status::StatusCode;
 
 extern crate regex;
-use regex::Regex;
+extern crate unicode_normalization;
+
+extern crate toml;
+
+extern crate rustc_serialize;
+extern crate flate2;
+extern crate memmap;
+
+#[macro_use]
+extern crate clap;
+use clap::{App, Arg, SubCommand};
 
 #[macro_use]
 extern crate lazy_static;
 
+use std::io::{self, Write};
+use std::process;
+
+mod assets;
 mod card;
-use card::Card;
+mod cli;
+mod collection;
+mod config;
+use config::Config;
+mod decklist;
+mod diff;
 mod error;
-use error::ProxygenError;
-
-const PROXYGEN_CSS: &'static str = include_str!("proxygen.css");
-const RESULTS_CSS: &'static str = include_str!("results.css");
-const MAX_CARDS: u64 = 1000;
-
-lazy_static!{
-    static ref BASE_RE: Regex = Regex::new(r"(\d+)?x?\s*(\D*?)\s*$").unwrap();
-    static ref SPLIT_RE: Regex = Regex::new(r"(.+?)\s*/+\s*.+").unwrap();
-}
-
-fn parse_decklist(decklist: &str) -> Result<Vec<(u64, Card)>, ProxygenError> {
-    let mut count = 0;
-    let mut out = Vec::new();
-    for entry in decklist.lines() {
-        let trimmed = entry.trim();
-        if !trimmed.is_empty() {
-            let (n, c) = match BASE_RE.captures(trimmed) {
-                Some(captures) => {
-                    let amount: u64 = match captures.at(1) {
-                        Some(v) => v.parse().unwrap(),
-                        None => 1,
-                    };
-
-                    count += amount;
-                    if count > MAX_CARDS {
-                        return Err(ProxygenError::TooManyCards);
-                    }
-
-                    let card_name = captures.at(2).unwrap();
-
-                    let sane_card_name = match SPLIT_RE.captures(card_name) {
-                        Some(split_captures) => split_captures.at(1).unwrap(),
-                        None => card_name,
-                    };
-
-                    let card = match card::Card::from_name(sane_card_name) {
-                        Ok(v) => v,
-                        Err(e) => {
-                            return Err(e);
-                        }
-                    };
-
-                    (amount, card)
-                }
-                None => return Err(ProxygenError::DecklistParseError(String::from(trimmed))),
-            };
-            out.push((n, c));
-        };
-    }
-    Ok(out)
-}
+mod format;
+use format::FORMATS;
+mod i18n;
+use i18n::LANGUAGES;
+mod permalink;
+mod render;
+mod server;
+mod sort;
+use sort::ORDERS;
+mod stats;
+mod store;
+mod theme;
+use theme::THEMES;
 
 fn main() {
-    println!("Building database..");
-    Card::from_name("Island").unwrap_or_else(|e| panic!("Error building database: {:?}", e));
-
-    let mut server = Nickel::new();
-
-    server.get("/proxygen",
-               middleware!(|_req, res| {
-        let mut doc = String::new();
-        html!(doc, html {
-            head {
-                meta charset="UTF-8"
-                title { "Proxygen" }
-                link href="https://fonts.googleapis.com/css?family=Inconsolata|Open+Sans"
-                     rel="stylesheet"
-                style {
-                    (PreEscaped(PROXYGEN_CSS))
-                }
-            }
-            body {
-                div id="surround" {
-                    div id="content" {
-                        h1 { "Simple Proxy Generator" }
-                        form method="post" {
-                            textarea name="decklist" class="decklist" {
-                              "1 Snapcaster Mage\r\n"
-                              "2x Ponder\r\n"
-                              "Dance of the Dead\r\n"
-                              "Stomping Ground\r\n"
-                              "Jace, the Mind Sculptor\r\n"
-                              "Delver of Secrets\r\n"
-                              "Ice\r\n"
-                              "Fire // Ice\r\n"
-                              "Akki Lavarunner\r\n"
-                              "Echo Mage\r\n"
-                              "Skysovereign, Consul Flagship\r\n"
-                              "Æthersnipe\r\n"
-                              "Aethersnipe\r\n"
-                              "Anafenza, Kin-Tree Spirit\r\n"
-                              "Anafenza Kin Tree Spirit\r\n"
-                            }
-                            input type="submit" /
-                        }
-                        p {
-                            "Please report any errors "
-                            "to the issue tracker on my "
-                            a href="https://github.com/Dryvnt/proxygen" {
-                                "Github project page"
-                            }
-                            "."
-                        }
-                    }
-                }
-            }
-        }).unwrap();
-        return res.send(doc)
-    }));
-
-    server.post("/proxygen",
-                middleware!(|req, mut res| {
-        let form_body = try_with!(res, req.form_body());
-        let decklist = String::from(match form_body.get("decklist") {
-            Some(v) => v,
-            None => {
-                *res.status_mut() = StatusCode::BadRequest;
-                return res.send("POST request form did not contain decklist")
-            }
-        });
-
-        let parsed = match parse_decklist(&decklist) {
-            Ok(v) => {
-                println!("{:?}", decklist);
-                v
-            },
-            Err(e) => {
-                println!("{:?}: {:?}", e, decklist);
-                match e {
-                    ProxygenError::TooManyCards => {
-                        *res.status_mut() = StatusCode::BadRequest;
-                        return res.send(format!("Too many proxies requested.
-                            Request at most {} proxies at a time", MAX_CARDS))
-                    }
-                    ProxygenError::InvalidCardName(s) => {
-                        *res.status_mut() = StatusCode::BadRequest;
-                        return res.send(format!("Invalid card name: {:?}", s));
-                    },
-                    ProxygenError::DecklistParseError(s) => {
-                        *res.status_mut() = StatusCode::BadRequest;
-                        return res.send(format!("Error parsing decklist at line: {:?}", s));
-                    },
-                    ProxygenError::MulticardHasMalformedNames(s) => {
-                        *res.status_mut() = StatusCode::InternalServerError;
-                        return res.send(format!("A split/flip/transform/meld card
-                            has less than two forms: {:?}", s))
-                    }
-                    e => {
-                        *res.status_mut() = StatusCode::InternalServerError;
-                        return res.send(format!("An error happened interally that wasn't
-                            handled properly. Tell the developer '{:?}'", e));
-                    }
-                }
-            }
-
-        };
-
-        let mut div_chain = String::new();
-
-        for pair in parsed {
-            let (n, card) = pair;
-            for _ in 0..n {
-                div_chain.push_str(&card.to_html());
-            }
+    let theme_names: Vec<&str> = THEMES.iter().map(|theme| theme.param()).collect();
+    let format_names: Vec<&str> = FORMATS.iter().map(|format| format.param()).collect();
+    let language_names: Vec<&str> = LANGUAGES.iter().map(|language| language.param()).collect();
+    let order_names: Vec<&str> = ORDERS.iter().map(|order| order.param()).collect();
+
+    let matches = App::new("proxygen")
+        .version(crate_version!())
+        .about("Simple proxy generator. Starts the web server unless given a subcommand.")
+        .arg(Arg::with_name("config")
+            .short("c")
+            .long("config")
+            .value_name("FILE")
+            .takes_value(true)
+            .help("TOML file to read settings from"))
+        .arg(Arg::with_name("address")
+            .long("address")
+            .takes_value(true)
+            .help("Address to listen on [default: 127.0.0.1]"))
+        .arg(Arg::with_name("port")
+            .short("p")
+            .long("port")
+            .takes_value(true)
+            .help("Port to listen on [default: 6767]"))
+        .arg(Arg::with_name("max-cards")
+            .long("max-cards")
+            .takes_value(true)
+            .help("Most proxies a single request may ask for [default: 1000]"))
+        .arg(Arg::with_name("max-body-size")
+            .long("max-body-size")
+            .takes_value(true)
+            .help("Largest accepted request body, in bytes [default: 262144]"))
+        .arg(Arg::with_name("default-theme")
+            .long("default-theme")
+            .takes_value(true)
+            .possible_values(&theme_names)
+            .help("Theme used when a request doesn't pick one"))
+        .arg(Arg::with_name("decks-dir")
+            .long("decks-dir")
+            .value_name("DIR")
+            .takes_value(true)
+            .help("Directory saved decks are kept in [default: decks]"))
+        .arg(Arg::with_name("card-index")
+            .long("card-index")
+            .value_name("FILE")
+            .takes_value(true)
+            .help("Card index made by build-db, used instead of the built-in card data if it \
+                   exists [default: cards.idx]"))
+        .subcommand(SubCommand::with_name("render")
+            .about("Renders a decklist to a proxy sheet without starting the web server")
+            .arg(Arg::with_name("DECKLIST")
+                .help("Decklist file to read, or - to read from stdin")
+                .required(true))
+            .arg(Arg::with_name("output")
+                .short("o")
+                .long("output")
+                .value_name("FILE")
+                .takes_value(true)
+                .help("Where to write the sheet. Files ending in .pdf are converted with \
+                       wkhtmltopdf. Defaults to stdout"))
+            .arg(Arg::with_name("theme")
+                .long("theme")
+                .takes_value(true)
+                .possible_values(&theme_names))
+            .arg(Arg::with_name("color-frames")
+                .long("color-frames")
+                .help("Tint card frames by color identity"))
+            .arg(Arg::with_name("card-language")
+                .long("card-language")
+                .value_name("LANG")
+                .takes_value(true)
+                .possible_values(&language_names)
+                .help("Print cards in this language, where the card data has it"))
+            .arg(Arg::with_name("format")
+                .long("format")
+                .takes_value(true)
+                .possible_values(&format_names)
+                .help("Check the deck against a format and list what isn't legal above the \
+                       proxies"))
+            .arg(Arg::with_name("stats")
+                .long("stats")
+                .help("Start the sheet with a page of deck statistics"))
+            .arg(Arg::with_name("order")
+                .long("order")
+                .takes_value(true)
+                .possible_values(&order_names)
+                .help("Order to print the cards in. Defaults to the order of the decklist"))
+            .arg(Arg::with_name("localize")
+                .long("localize")
+                .conflicts_with("card-language")
+                .help("Print cards listed by a translated name in that language"))
+            .arg(Arg::with_name("collection")
+                .long("collection")
+                .value_name("FILE")
+                .takes_value(true)
+                .help("Collection list or CSV export. Cards in it are not proxied")))
+        .subcommand(SubCommand::with_name("build-db")
+            .about("Saves the built-in card data as a card index, which loads much faster")
+            .arg(Arg::with_name("OUTPUT")
+                .help("File to write the index to [default: the card-index setting]")))
+        .get_matches();
+
+    let config = match Config::load(&matches) {
+        Ok(v) => v,
+        Err(e) => {
+            writeln!(io::stderr(), "{}", e).unwrap();
+            process::exit(1);
         }
-
-        let mut doc = String::new();
-        html!(doc, html {
-            head {
-                meta charset="UTF-8"
-                title { "Proxygen" }
-                link href="https://fonts.googleapis.com/css?family=Open+Sans" rel="stylesheet"
-                style {
-                    (PreEscaped(RESULTS_CSS))
-                }
+    };
+    card::use_index(&config.card_index);
+
+    match matches.subcommand() {
+        ("render", Some(sub_matches)) => {
+            if let Err(e) = cli::render(&config, sub_matches) {
+                writeln!(io::stderr(), "Error: {}", e).unwrap();
+                process::exit(1);
             }
-            body {
-                (PreEscaped(div_chain))
+        }
+        ("build-db", Some(sub_matches)) => {
+            if let Err(e) = cli::build_db(&config, sub_matches) {
+                writeln!(io::stderr(), "Error: {}", e).unwrap();
+                process::exit(1);
             }
-        }).unwrap();
-        return res.send(doc)
-    }));
-
-    server.listen("127.0.0.1:6767");
+        }
+        _ => server::run(config),
+    }
 }
@@ -0,0 +1,46 @@
+use std::io::{Read, Write};
+
+use super::flate2::Compression;
+use super::flate2::read::DeflateDecoder;
+use super::flate2::write::DeflateEncoder;
+use super::rustc_serialize::base64::{FromBase64, ToBase64, URL_SAFE};
+
+use super::error::ProxygenError;
+use super::render::RenderOptions;
+
+// Permalinks carry the whole decklist, deflated and base64 encoded, so
+// there's nothing to store on the server and links never expire.
+
+pub fn encode(decklist: &str) -> String {
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::Best);
+    encoder.write_all(decklist.as_bytes()).unwrap();
+    encoder.finish().unwrap().to_base64(URL_SAFE)
+}
+
+/// Decodes a permalink id back into a decklist. `max_len` bounds the
+/// inflated size, the same way the request body size is bounded.
+pub fn decode(id: &str, max_len: u64) -> Result<String, ProxygenError> {
+    let compressed = try!(id.from_base64()
+        .map_err(|_| ProxygenError::InvalidPermalink(String::from(id))));
+
+    let mut decklist = String::new();
+    try!(DeflateDecoder::new(&compressed[..])
+        .take(max_len + 1)
+        .read_to_string(&mut decklist)
+        .map_err(|_| ProxygenError::InvalidPermalink(String::from(id))));
+
+    if decklist.len() as u64 > max_len {
+        return Err(ProxygenError::InvalidPermalink(String::from(id)));
+    }
+    Ok(decklist)
+}
+
+/// Link that reproduces a sheet.
+pub fn sheet_path(id: &str, options: &RenderOptions) -> String {
+    format!("/proxygen/s/{}?{}", id, options.query_string())
+}
+
+/// Link to the form, pre-filled with a sheet's decklist and options.
+pub fn edit_path(id: &str, options: &RenderOptions) -> String {
+    format!("/proxygen?d={}&{}", id, options.query_string())
+}
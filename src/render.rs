@@ -0,0 +1,572 @@
+use std::sync::Arc;
+
+use super::maud::PreEscaped;
+
+use super::assets;
+use super::card::{Card, WUBRG};
+use super::error::{ErrorReport, ProxygenError};
+use super::format::{Format, FORMATS};
+use super::i18n::{CardLanguage, Language, LANGUAGES};
+use super::permalink;
+use super::sort::{Order, ORDERS};
+use super::stats::DeckStats;
+use super::store::SavedDeck;
+use super::theme::{Theme, THEMES};
+
+pub const EXAMPLE_DECKLIST: &'static str = concat!("1 Snapcaster Mage\r\n",
+                                                   "2x Ponder\r\n",
+                                                   "Dance of the Dead\r\n",
+                                                   "Stomping Ground\r\n",
+                                                   "Jace, the Mind Sculptor\r\n",
+                                                   "Delver of Secrets\r\n",
+                                                   "Ice\r\n",
+                                                   "Fire // Ice\r\n",
+                                                   "Akki Lavarunner\r\n",
+                                                   "Echo Mage\r\n",
+                                                   "Skysovereign, Consul Flagship\r\n",
+                                                   "Æthersnipe\r\n",
+                                                   "Aethersnipe\r\n",
+                                                   "Anafenza, Kin-Tree Spirit\r\n",
+                                                   "Anafenza Kin Tree Spirit\r\n",
+                                                   "\r\n",
+                                                   "Sideboard\r\n",
+                                                   "2 Duress\r\n");
+
+/// Per-request choices for how the proxies are drawn.
+#[derive(Debug, Clone, Default)]
+pub struct RenderOptions {
+    pub theme: Theme,
+    pub color_frames: bool,
+    /// Language of the page and of the card placeholders.
+    pub language: Language,
+    pub card_language: CardLanguage,
+    /// Format to check the deck against, if any.
+    pub format: Option<Format>,
+    /// Start the sheet with a page of deck statistics.
+    pub stats: bool,
+    /// Order the proxies are printed in.
+    pub order: Order,
+    /// Embed stylesheets and fonts in the page instead of linking the
+    /// server's copies.
+    pub inline_assets: bool,
+}
+
+impl RenderOptions {
+    /// The options as form fields, for links that should keep them.
+    pub fn query_string(&self) -> String {
+        let mut query = format!("theme={}", self.theme.param());
+        if self.color_frames {
+            query.push_str("&color_frames=on");
+        }
+        if self.language != Language::default() {
+            query.push_str(&format!("&lang={}", self.language.param()));
+        }
+        if self.card_language != CardLanguage::default() {
+            query.push_str(&format!("&cards={}", self.card_language.param()));
+        }
+        if let Some(format) = self.format {
+            query.push_str(&format!("&format={}", format.param()));
+        }
+        if self.stats {
+            query.push_str("&stats=on");
+        }
+        if self.order != Order::default() {
+            query.push_str(&format!("&order={}", self.order.param()));
+        }
+        query
+    }
+}
+
+/// A titled list of notes shown above the proxies, like what a diff removed.
+#[derive(Debug, Clone)]
+pub struct Report {
+    pub title: String,
+    pub lines: Vec<String>,
+}
+
+impl Report {
+    fn to_html(&self) -> String {
+        let mut s = String::new();
+        html!(s,
+            div class="report" {
+                h2 { (self.title) }
+                ul {
+                    @for line in &self.lines {
+                        li { (line) }
+                    }
+                }
+            }
+        )
+            .unwrap();
+        s
+    }
+}
+
+/// The decklist form, along with everything it may be pre-filled with.
+pub struct FormPage {
+    pub decklist: String,
+    pub collection: String,
+    pub deck_name: String,
+    pub options: RenderOptions,
+    pub saved_decks: Vec<(String, SavedDeck)>,
+    /// Problems with the last submission, shown above the form.
+    pub errors: Vec<ErrorReport>,
+}
+
+/// The error messages, and the decklist with the lines they're about
+/// marked, so they can be found and fixed in place.
+fn errors_html(errors: &[ErrorReport], decklist: &str) -> String {
+    let bad_lines: Vec<usize> = errors.iter().filter_map(|e| e.line).collect();
+
+    let mut s = String::new();
+    html!(s,
+        div class="errors" {
+            ul {
+                @for error in errors {
+                    li { (error.message) }
+                }
+            }
+            @if !bad_lines.is_empty() {
+                ol class="decklist_preview" {
+                    @for (i, line) in decklist.lines().enumerate() {
+                        @if bad_lines.contains(&(i + 1)) {
+                            li class="bad_line" { (line) }
+                        } @else {
+                            li { (line) }
+                        }
+                    }
+                }
+            }
+        }
+    )
+        .unwrap();
+    s
+}
+
+impl FormPage {
+    pub fn new(decklist: &str, options: &RenderOptions) -> FormPage {
+        FormPage {
+            decklist: String::from(decklist),
+            collection: String::new(),
+            deck_name: String::new(),
+            options: options.clone(),
+            saved_decks: Vec::new(),
+            errors: Vec::new(),
+        }
+    }
+
+    fn card_language_select(&self) -> String {
+        let strings = self.options.language.strings();
+        let as_listed = self.options.card_language == CardLanguage::AsListed;
+        let mut s = String::new();
+        html!(s,
+            select name="cards" id="cards" {
+                @for language in LANGUAGES.iter() {
+                    @if self.options.card_language == CardLanguage::In(*language) {
+                        option value=(language.param()) selected="selected" {
+                            (language.label())
+                        }
+                    } @else {
+                        option value=(language.param()) { (language.label()) }
+                    }
+                }
+                @if as_listed {
+                    option value="as_listed" selected="selected" { (strings.as_listed) }
+                } @else {
+                    option value="as_listed" { (strings.as_listed) }
+                }
+            }
+        )
+            .unwrap();
+        s
+    }
+
+    fn format_select(&self) -> String {
+        let strings = self.options.language.strings();
+        let mut s = String::new();
+        html!(s,
+            select name="format" id="format" {
+                option value="" { (strings.no_format) }
+                @for format in FORMATS.iter() {
+                    @if self.options.format == Some(*format) {
+                        option value=(format.param()) selected="selected" { (format.name()) }
+                    } @else {
+                        option value=(format.param()) { (format.name()) }
+                    }
+                }
+            }
+        )
+            .unwrap();
+        s
+    }
+
+    fn order_select(&self) -> String {
+        let strings = self.options.language.strings();
+        let mut s = String::new();
+        html!(s,
+            select name="order" id="order" {
+                @for (order, label) in ORDERS.iter().zip(&strings.orders) {
+                    @if *order == self.options.order {
+                        option value=(order.param()) selected="selected" { (label) }
+                    } @else {
+                        option value=(order.param()) { (label) }
+                    }
+                }
+            }
+        )
+            .unwrap();
+        s
+    }
+
+    /// The card search panel. search.js does the searching, so without
+    /// scripts it does nothing.
+    fn search_html(&self) -> String {
+        let strings = self.options.language.strings();
+        let mut s = String::new();
+        html!(s,
+            details id="search" data-add=(strings.add_card) data-no-results=(strings.no_results) {
+                summary { (strings.search) }
+                p {
+                    label for="search_name" { (strings.search_name) " " }
+                    input type="text" id="search_name" /
+                }
+                p {
+                    label for="search_type" { (strings.search_type) " " }
+                    input type="text" id="search_type" /
+                }
+                p {
+                    label for="search_oracle" { (strings.search_oracle) " " }
+                    input type="text" id="search_oracle" /
+                }
+                p {
+                    label for="search_mana_value" { (strings.search_mana_value) " " }
+                    input type="number" id="search_mana_value" min="0" /
+                }
+                p {
+                    (strings.search_colors) " "
+                    @for (color, name) in WUBRG.iter().zip(&strings.color_names) {
+                        label {
+                            input type="checkbox" name="search_color" value=(color.letter()) /
+                            " " (name) " "
+                        }
+                    }
+                }
+                button type="button" id="search_button" { (strings.search_button) }
+                ul id="search_results" {}
+            }
+        )
+            .unwrap();
+        s
+    }
+
+    fn saved_decks_html(&self) -> String {
+        let strings = self.options.language.strings();
+        let query = self.options.query_string();
+        let mut s = String::new();
+        html!(s,
+            h2 { (strings.saved_decks) }
+            table class="saved_decks" {
+                @for &(ref slug, ref deck) in &self.saved_decks {
+                    tr {
+                        td class="deck_name" { (deck.name) }
+                        td {
+                            a href=(format!("/proxygen/decks/{}?{}", slug, query)) {
+                                (strings.print)
+                            }
+                            " "
+                            a href=(format!("/proxygen?deck={}&{}", slug, query)) {
+                                (strings.edit)
+                            }
+                        }
+                        td {
+                            form method="post" action=(format!("/proxygen/decks/{}/rename", slug)) {
+                                input type="text" name="name" value=(deck.name) /
+                                input type="submit" value=(strings.rename) /
+                            }
+                        }
+                        td {
+                            form method="post" action=(format!("/proxygen/decks/{}/delete", slug)) {
+                                input type="submit" value=(strings.delete) /
+                            }
+                        }
+                    }
+                }
+            }
+        )
+            .unwrap();
+        s
+    }
+
+    pub fn to_html(&self) -> String {
+        let errors = if self.errors.is_empty() {
+            String::new()
+        } else {
+            errors_html(&self.errors, &self.decklist)
+        };
+        let saved_decks = if self.saved_decks.is_empty() {
+            String::new()
+        } else {
+            self.saved_decks_html()
+        };
+        let strings = self.options.language.strings();
+
+        let mut doc = String::new();
+        html!(doc, html lang=(self.options.language.param()) {
+            head {
+                meta charset="UTF-8"
+                title { "Proxygen" }
+                style {
+                    (PreEscaped(assets::font_css(false)))
+                }
+                (PreEscaped(assets::stylesheet("proxygen.css", false)))
+                (PreEscaped(assets::script("search.js")))
+                (PreEscaped(assets::script("complete.js")))
+            }
+            body {
+                div id="surround" {
+                    div id="content" {
+                        h1 { (strings.heading) }
+                        (PreEscaped(errors))
+                        form method="post" action="/proxygen" {
+                            textarea name="decklist" id="decklist" class="decklist" {
+                                (self.decklist)
+                            }
+                            ul id="suggestions" {}
+                            p {
+                                label for="theme" { (strings.theme) " " }
+                                select name="theme" id="theme" {
+                                    @for (theme, label) in THEMES.iter().zip(&strings.themes) {
+                                        @if *theme == self.options.theme {
+                                            option value=(theme.param()) selected="selected" {
+                                                (label)
+                                            }
+                                        } @else {
+                                            option value=(theme.param()) { (label) }
+                                        }
+                                    }
+                                }
+                            }
+                            p {
+                                @if self.options.color_frames {
+                                    input type="checkbox" name="color_frames" id="color_frames"
+                                          checked="checked" /
+                                } @else {
+                                    input type="checkbox" name="color_frames" id="color_frames" /
+                                }
+                                label for="color_frames" { " " (strings.color_frames) }
+                            }
+                            p {
+                                label for="cards" { (strings.card_language) " " }
+                                (PreEscaped(self.card_language_select()))
+                            }
+                            p {
+                                @if self.options.stats {
+                                    input type="checkbox" name="stats" id="stats"
+                                          checked="checked" /
+                                } @else {
+                                    input type="checkbox" name="stats" id="stats" /
+                                }
+                                label for="stats" { " " (strings.stats) }
+                            }
+                            p {
+                                label for="format" { (strings.format) " " }
+                                (PreEscaped(self.format_select()))
+                            }
+                            p {
+                                label for="order" { (strings.order) " " }
+                                (PreEscaped(self.order_select()))
+                            }
+                            p {
+                                label for="lang" { (strings.page_language) " " }
+                                (PreEscaped(language_select("lang", self.options.language)))
+                            }
+                            details {
+                                summary { (strings.skip_owned) }
+                                p { (strings.skip_owned_help) }
+                                textarea name="collection" class="collection" {
+                                    (self.collection)
+                                }
+                            }
+                            input type="submit" value=(strings.submit) /
+                            p class="save_deck" {
+                                input type="text" name="name" placeholder=(strings.deck_name)
+                                      value=(self.deck_name) /
+                                button type="submit" formaction="/proxygen/decks" {
+                                    (strings.save_deck)
+                                }
+                            }
+                        }
+                        (PreEscaped(self.search_html()))
+                        (PreEscaped(saved_decks))
+                        p {
+                            a href=(format!("/proxygen/diff?{}", self.options.query_string())) {
+                                (strings.diff_link)
+                            }
+                        }
+                        p {
+                            (strings.report_errors)
+                            a href="https://github.com/Dryvnt/proxygen" {
+                                (strings.project_page)
+                            }
+                            (strings.report_errors_end)
+                        }
+                    }
+                }
+            }
+        }).unwrap();
+        doc
+    }
+}
+
+fn language_select(name: &str, selected: Language) -> String {
+    let mut s = String::new();
+    html!(s,
+        select name=(name) id=(name) {
+            @for language in LANGUAGES.iter() {
+                @if *language == selected {
+                    option value=(language.param()) selected="selected" { (language.label()) }
+                } @else {
+                    option value=(language.param()) { (language.label()) }
+                }
+            }
+        }
+    )
+        .unwrap();
+    s
+}
+
+fn toolbar_html(id: &str, options: &RenderOptions) -> String {
+    let strings = options.language.strings();
+    let mut s = String::new();
+    html!(s,
+        div class="toolbar" {
+            a href=(permalink::sheet_path(id, options)) { (strings.sheet_link) }
+            " | "
+            a href=(permalink::edit_path(id, options)) { (strings.edit_link) }
+        }
+    )
+        .unwrap();
+    s
+}
+
+/// Two decklists, for proxying only what the new one adds. Problems with
+/// either list from the last submission are shown above it.
+pub fn diff_form_page(old: &str,
+                      new: &str,
+                      options: &RenderOptions,
+                      old_errors: &[ErrorReport],
+                      new_errors: &[ErrorReport])
+                      -> String {
+    let strings = options.language.strings();
+    let old_errors_html = if old_errors.is_empty() {
+        String::new()
+    } else {
+        errors_html(old_errors, old)
+    };
+    let new_errors_html = if new_errors.is_empty() {
+        String::new()
+    } else {
+        errors_html(new_errors, new)
+    };
+    let format = options.format.map(|format| format.param()).unwrap_or("");
+    let mut doc = String::new();
+    html!(doc, html lang=(options.language.param()) {
+        head {
+            meta charset="UTF-8"
+            title { "Proxygen" }
+            style {
+                (PreEscaped(assets::font_css(false)))
+            }
+            (PreEscaped(assets::stylesheet("proxygen.css", false)))
+        }
+        body {
+            div id="surround" {
+                div id="content" {
+                    h1 { (strings.diff_heading) }
+                    form method="post" action="/proxygen/diff" {
+                        label for="old" { (strings.old_list) }
+                        (PreEscaped(old_errors_html))
+                        textarea name="old" id="old" class="decklist" { (old) }
+                        label for="new" { (strings.new_list) }
+                        (PreEscaped(new_errors_html))
+                        textarea name="new" id="new" class="decklist" { (new) }
+                        input type="hidden" name="theme" value=(options.theme.param()) /
+                        @if options.color_frames {
+                            input type="hidden" name="color_frames" value="on" /
+                        }
+                        input type="hidden" name="lang" value=(options.language.param()) /
+                        input type="hidden" name="cards" value=(options.card_language.param()) /
+                        @if !format.is_empty() {
+                            input type="hidden" name="format" value=(format) /
+                        }
+                        @if options.stats {
+                            input type="hidden" name="stats" value="on" /
+                        }
+                        input type="hidden" name="order" value=(options.order.param()) /
+                        input type="submit" value=(strings.submit) /
+                    }
+                    p {
+                        a href=(format!("/proxygen?{}", options.query_string())) {
+                            (strings.back)
+                        }
+                    }
+                }
+            }
+        }
+    }).unwrap();
+    doc
+}
+
+/// The printable sheet. `permalink` is the sheet's permalink id, if it has
+/// one, and adds a (non-printing) bar linking to it. `reports` are shown
+/// above the proxies, and `stats` on a page of their own before them.
+pub fn results_page(cards: &[(u64, Arc<Card>)],
+                    options: &RenderOptions,
+                    permalink: Option<&str>,
+                    reports: &[Report],
+                    stats: Option<&DeckStats>)
+                    -> Result<String, ProxygenError> {
+    let mut div_chain = String::new();
+
+    for &(n, ref card) in cards {
+        let card_html = try!(card.to_html(options.theme.text_scale(),
+                                          options.language.strings()));
+        for _ in 0..n {
+            div_chain.push_str(&card_html);
+        }
+    }
+
+    let inline = options.inline_assets;
+    let theme_css = options.theme
+        .stylesheet()
+        .map(|name| assets::stylesheet(name, inline))
+        .unwrap_or_default();
+
+    let toolbar = permalink.map(|id| toolbar_html(id, options)).unwrap_or_default();
+    let reports_html: String = reports.iter().map(Report::to_html).collect();
+    let cover_sheet = stats.map(|stats| stats.to_html(options.language.strings()))
+        .unwrap_or_default();
+
+    let mut doc = String::new();
+    html!(doc, html lang=(options.language.param()) {
+        head {
+            meta charset="UTF-8"
+            title { "Proxygen" }
+            style {
+                (PreEscaped(assets::font_css(inline)))
+            }
+            (PreEscaped(assets::stylesheet("results.css", inline)))
+            @if options.color_frames {
+                (PreEscaped(assets::stylesheet("frames.css", inline)))
+            }
+            (PreEscaped(theme_css))
+        }
+        body {
+            (PreEscaped(toolbar))
+            (PreEscaped(cover_sheet))
+            (PreEscaped(reports_html))
+            (PreEscaped(div_chain))
+        }
+    }).unwrap();
+    Ok(doc)
+}
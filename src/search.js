@@ -0,0 +1,78 @@
+// Card search panel on the form page. Asks /proxygen/api/search for cards
+// matching the filters and lists them with buttons that add them to the
+// decklist.
+(function () {
+    var panel = document.getElementById("search");
+    var decklist = document.getElementById("decklist");
+    if (!panel || !decklist) {
+        return;
+    }
+    var results = document.getElementById("search_results");
+
+    function field(name) {
+        return document.getElementById("search_" + name).value;
+    }
+
+    function addCard(name) {
+        var text = decklist.value;
+        if (text.length > 0 && text.charAt(text.length - 1) !== "\n") {
+            text += "\n";
+        }
+        decklist.value = text + "1 " + name + "\n";
+    }
+
+    function show(names) {
+        results.innerHTML = "";
+        if (names.length === 0) {
+            var empty = document.createElement("li");
+            empty.textContent = panel.getAttribute("data-no-results");
+            results.appendChild(empty);
+        }
+        names.forEach(function (name) {
+            var item = document.createElement("li");
+            var button = document.createElement("button");
+            button.type = "button";
+            button.textContent = panel.getAttribute("data-add");
+            button.addEventListener("click", function () {
+                addCard(name);
+            });
+            item.appendChild(button);
+            item.appendChild(document.createTextNode(" " + name));
+            results.appendChild(item);
+        });
+    }
+
+    function showError(message) {
+        results.innerHTML = "";
+        var item = document.createElement("li");
+        item.className = "error";
+        item.textContent = message;
+        results.appendChild(item);
+    }
+
+    document.getElementById("search_button").addEventListener("click", function () {
+        var colors = "";
+        var boxes = panel.querySelectorAll("input[name=search_color]:checked");
+        for (var i = 0; i < boxes.length; i++) {
+            colors += boxes[i].value;
+        }
+        var query = ["name=" + encodeURIComponent(field("name")),
+                     "type=" + encodeURIComponent(field("type")),
+                     "oracle=" + encodeURIComponent(field("oracle")),
+                     "mana_value=" + encodeURIComponent(field("mana_value")),
+                     "colors=" + colors];
+
+        var request = new XMLHttpRequest();
+        request.open("GET", "/proxygen/api/search?" + query.join("&"));
+        request.setRequestHeader("Accept", "application/json");
+        request.onload = function () {
+            var body = JSON.parse(request.responseText);
+            if (request.status === 200) {
+                show(body);
+            } else {
+                showError(body.message);
+            }
+        };
+        request.send();
+    });
+})();
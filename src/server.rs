@@ -0,0 +1,557 @@
+use std::io::{self, Write};
+use std::process;
+
+use super::nickel::{Nickel, HttpRouter, FormBody, MediaType, MiddlewareResult, Params,
+                    QueryString, Request, Response};
+use super::nickel::status::StatusCode;
+use super::nickel::hyper::header::{CacheControl, CacheDirective, ContentLength, ContentType,
+                                  ETag, EntityTag, IfNoneMatch, Location, Accept};
+use super::nickel::hyper::mime::{TopLevel, SubLevel};
+use super::regex::Regex;
+use super::serde_json;
+
+use super::assets::{Asset, ASSETS};
+use super::card::{self, Card, Color, Search};
+use super::collection::Collection;
+use super::config::Config;
+use super::decklist::{parse_decklist, Section};
+use super::diff;
+use super::error::ProxygenError;
+use super::format::{self, Format};
+use super::i18n::{CardLanguage, Language, Strings};
+use super::permalink;
+use super::render::{self, FormPage, RenderOptions, Report};
+use super::sort::{self, Order};
+use super::stats::DeckStats;
+use super::store::{DeckListing, DeckStore, SavedDeck};
+use super::theme::Theme;
+
+/// Most cards a search sends back.
+const SEARCH_LIMIT: usize = 50;
+
+/// Most names suggested for what's been typed so far.
+const COMPLETE_LIMIT: usize = 10;
+
+// Asset URLs change with their contents, so they never need revalidating.
+const ASSET_MAX_AGE: u32 = 365 * 24 * 60 * 60;
+
+fn is_fresh(req: &Request, asset: &Asset) -> bool {
+    match req.origin.headers.get::<IfNoneMatch>() {
+        Some(&IfNoneMatch::Any) => true,
+        Some(&IfNoneMatch::Items(ref tags)) => {
+            tags.iter().any(|tag| tag.tag() == asset.version())
+        }
+        None => false,
+    }
+}
+
+/// Checks the declared body size against the configured limit, so oversized
+/// posts are turned away before nickel reads them into memory. Nickel reads
+/// the whole body with no limit of its own, so a post that doesn't declare
+/// its size, like a chunked one, is refused rather than read.
+fn check_body_size(req: &Request, max_body_size: u64) -> Result<(), ProxygenError> {
+    match req.origin.headers.get::<ContentLength>() {
+        Some(&ContentLength(length)) if length > max_body_size => {
+            Err(ProxygenError::BodyTooLarge(max_body_size))
+        }
+        Some(_) => Ok(()),
+        None => Err(ProxygenError::LengthRequired),
+    }
+}
+
+/// Whether the client asked for JSON, in which case errors are sent as a
+/// serialized ErrorReport instead of a page.
+fn wants_json(req: &Request) -> bool {
+    match req.origin.headers.get::<Accept>() {
+        Some(&Accept(ref items)) => {
+            items.iter().any(|item| {
+                item.item.0 == TopLevel::Application && item.item.1 == SubLevel::Json
+            })
+        }
+        None => false,
+    }
+}
+
+fn status(e: &ProxygenError) -> StatusCode {
+    match *e {
+        ProxygenError::BodyTooLarge(_) => StatusCode::PayloadTooLarge,
+        ProxygenError::LengthRequired => StatusCode::LengthRequired,
+        ProxygenError::DeckNotFound(_) => StatusCode::NotFound,
+        ref e if e.is_user_error() => StatusCode::BadRequest,
+        _ => StatusCode::InternalServerError,
+    }
+}
+
+fn send_error<'mw>(mut res: Response<'mw>,
+                   e: &ProxygenError,
+                   json: bool)
+                   -> MiddlewareResult<'mw> {
+    *res.status_mut() = status(e);
+    if json {
+        res.set(MediaType::Json);
+        res.send(serde_json::to_string(&e.report()).unwrap())
+    } else {
+        // Messages quote what was sent, like a deck slug from the URL, so
+        // they must not be read as HTML.
+        res.set(MediaType::Txt);
+        res.send(e.to_string())
+    }
+}
+
+/// The form again, as it was submitted, with the error shown above it.
+fn form_with_error(mut form: FormPage, e: &ProxygenError) -> String {
+    let strings = form.options.language.strings();
+    form.errors = e.parts().into_iter().map(|e| e.report_in(strings)).collect();
+    form.to_html()
+}
+
+fn saved_decks(store: &DeckStore) -> Vec<(String, SavedDeck)> {
+    match store.list() {
+        Ok(v) => v,
+        Err(e) => {
+            println!("Error listing saved decks: {:?}", e);
+            Vec::new()
+        }
+    }
+}
+
+fn missing_field(name: &str) -> ProxygenError {
+    ProxygenError::MissingField(String::from(name))
+}
+
+fn card_language(params: &Params) -> CardLanguage {
+    match params.get("cards").and_then(CardLanguage::from_param) {
+        Some(v) => v,
+        // What links made before there was a choice of language used.
+        None if params.get("localize").is_some() => CardLanguage::AsListed,
+        None => CardLanguage::default(),
+    }
+}
+
+/// The render options in a query string or form.
+fn render_options(params: &Params, default_theme: Theme) -> RenderOptions {
+    RenderOptions {
+        theme: params.get("theme").and_then(Theme::from_param).unwrap_or(default_theme),
+        color_frames: params.get("color_frames").is_some(),
+        language: params.get("lang").and_then(Language::from_param).unwrap_or_default(),
+        card_language: card_language(params),
+        format: params.get("format").and_then(Format::from_param),
+        stats: params.get("stats").is_some(),
+        order: params.get("order").and_then(Order::from_param).unwrap_or_default(),
+        inline_assets: false,
+    }
+}
+
+/// The card search filters in a query string. Empty fields are left out.
+fn search_filters(params: &Params) -> Result<Search, ProxygenError> {
+    let field = |name: &str| {
+        params.get(name).map(str::trim).and_then(|v| if v.is_empty() { None } else { Some(v) })
+    };
+    let invalid = |v: &str| ProxygenError::InvalidSearch(String::from(v));
+
+    let oracle = match field("oracle") {
+        Some(pattern) => {
+            Some(try!(Regex::new(&format!("(?i){}", pattern)).map_err(|_| invalid(pattern))))
+        }
+        None => None,
+    };
+    let mana_value = match field("mana_value") {
+        Some(v) => Some(try!(v.parse().map_err(|_| invalid(v)))),
+        None => None,
+    };
+    let mut colors = Vec::new();
+    for letter in field("colors").unwrap_or("").chars() {
+        let letter = letter.to_uppercase().collect::<String>();
+        colors.push(try!(Color::from_letter(&letter).ok_or_else(|| invalid(&letter))));
+    }
+
+    Ok(Search {
+        name: field("name").map(String::from),
+        typeline: field("type").map(String::from),
+        oracle: oracle,
+        mana_value: mana_value,
+        colors: colors,
+    })
+}
+
+fn redirect<'mw>(mut res: Response<'mw>, location: String) -> MiddlewareResult<'mw> {
+    res.set(Location(location));
+    *res.status_mut() = StatusCode::SeeOther;
+    res.send("")
+}
+
+/// Parses a decklist into its sections, for `sort::arrange` to put in the
+/// order the options ask for.
+fn parse(decklist: &str,
+         options: &RenderOptions,
+         max_cards: u64)
+         -> Result<Vec<Section>, ProxygenError> {
+    match parse_decklist(decklist, max_cards, options.card_language) {
+        Ok(v) => {
+            println!("{:?}", decklist);
+            Ok(v)
+        }
+        Err(e) => {
+            println!("{:?}: {:?}", e, decklist);
+            Err(e)
+        }
+    }
+}
+
+/// Report of the cards a collection saved us from proxying.
+fn skipped_report(skipped: &[(u64, String)], strings: &Strings) -> Report {
+    Report {
+        title: String::from(strings.skipped),
+        lines: if skipped.is_empty() {
+            vec![String::from(strings.nothing_skipped)]
+        } else {
+            skipped.iter().map(|&(n, ref name)| format!("{} {}", n, name)).collect()
+        },
+    }
+}
+
+/// Parses a decklist and renders its sheet, leaving out cards the
+/// collection already has, if one was given. The whole deck is checked
+/// against the chosen format, before anything is left out.
+fn make_sheet(decklist: &str,
+              collection: Option<&str>,
+              options: &RenderOptions,
+              max_cards: u64)
+              -> Result<String, ProxygenError> {
+    let sections = try!(parse(decklist, options, max_cards));
+    let strings = options.language.strings();
+
+    let mut reports = Vec::new();
+    if let Some(format) = options.format {
+        reports.push(format::report(format, &sections, strings));
+    }
+    let stats = if options.stats {
+        Some(DeckStats::new(&sections))
+    } else {
+        None
+    };
+    let parsed = sort::arrange(sections, options.order);
+
+    let collection = collection.map(Collection::parse).unwrap_or_default();
+    if collection.is_empty() {
+        let id = permalink::encode(decklist);
+        render::results_page(&parsed, options, Some(&id), &reports, stats.as_ref())
+    } else {
+        // Permalinks only carry the decklist, so they can't reproduce this.
+        let (remaining, skipped) = collection.subtract(parsed);
+        reports.push(skipped_report(&skipped, strings));
+        render::results_page(&remaining, options, None, &reports, stats.as_ref())
+    }
+}
+
+pub fn run(config: Config) {
+    println!("Loading card database..");
+    if let Err(e) = Card::from_name("Island") {
+        writeln!(io::stderr(), "Error building database: {}", e).unwrap();
+        process::exit(1);
+    }
+
+    let max_cards = config.max_cards;
+    let max_body_size = config.max_body_size;
+    let default_theme = config.default_theme;
+    let store = match DeckStore::open(&config.decks_dir) {
+        Ok(v) => v,
+        Err(e) => {
+            writeln!(io::stderr(), "Error opening deck store: {}", e).unwrap();
+            process::exit(1);
+        }
+    };
+
+    let mut server = Nickel::new();
+
+    {
+        let store = store.clone();
+        server.get("/proxygen",
+                   middleware!(|req, mut res| {
+            let json = wants_json(req);
+            let query = req.query();
+            let options = render_options(query, default_theme);
+            let mut form = FormPage::new(render::EXAMPLE_DECKLIST, &options);
+
+            // Permalinks' "Edit this list" links pre-fill the form with their list.
+            if let Some(id) = query.get("d") {
+                form.decklist = match permalink::decode(id, max_body_size) {
+                    Ok(v) => v,
+                    Err(e) => return send_error(res, &e, json),
+                };
+            }
+
+            if let Some(slug) = query.get("deck") {
+                match store.load(slug) {
+                    Ok(deck) => {
+                        form.decklist = deck.decklist;
+                        form.deck_name = deck.name;
+                    }
+                    Err(e) => return send_error(res, &e, json),
+                }
+            }
+
+            form.saved_decks = saved_decks(&store);
+
+            return res.send(form.to_html())
+        }));
+    }
+
+    {
+        let store = store.clone();
+        server.post("/proxygen",
+                    middleware!(|req, mut res| {
+            let json = wants_json(req);
+            if let Err(e) = check_body_size(req, max_body_size) {
+                return send_error(res, &e, json)
+            }
+
+            let form_body = try_with!(res, req.form_body());
+            let decklist = String::from(match form_body.get("decklist") {
+                Some(v) => v,
+                None => return send_error(res, &missing_field("decklist"), json),
+            });
+            let collection = form_body.get("collection");
+
+            let options = render_options(form_body, default_theme);
+
+            match make_sheet(&decklist, collection, &options, max_cards) {
+                Ok(doc) => return res.send(doc),
+                Err(ref e) if !json => {
+                    let mut form = FormPage::new(&decklist, &options);
+                    form.collection = String::from(collection.unwrap_or(""));
+                    form.saved_decks = saved_decks(&store);
+                    *res.status_mut() = status(e);
+                    return res.send(form_with_error(form, e))
+                }
+                Err(e) => return send_error(res, &e, json),
+            }
+        }));
+    }
+
+    server.get("/proxygen/diff",
+               middleware!(|req, res| {
+        let query = req.query();
+        let options = render_options(query, default_theme);
+        return res.send(render::diff_form_page("", "", &options, &[], &[]))
+    }));
+
+    server.post("/proxygen/diff",
+                middleware!(|req, mut res| {
+        let json = wants_json(req);
+        if let Err(e) = check_body_size(req, max_body_size) {
+            return send_error(res, &e, json)
+        }
+
+        let form_body = try_with!(res, req.form_body());
+        let (old, new) = match (form_body.get("old"), form_body.get("new")) {
+            (Some(old), Some(new)) => (old, new),
+            (None, _) => return send_error(res, &missing_field("old"), json),
+            (_, None) => return send_error(res, &missing_field("new"), json),
+        };
+        let options = render_options(form_body, default_theme);
+
+        let parsed = match (parse(old, &options, max_cards), parse(new, &options, max_cards)) {
+            (Ok(old), Ok(new)) => (old, new),
+            (old_result, new_result) => {
+                // Both lists are checked, so the problems in each can be
+                // fixed in one go.
+                let strings = options.language.strings();
+                let reports = |result: &Result<_, ProxygenError>| match *result {
+                    Ok(_) => Vec::new(),
+                    Err(ref e) => e.parts().into_iter().map(|e| e.report_in(strings)).collect(),
+                };
+                let (old_errors, new_errors) = (reports(&old_result), reports(&new_result));
+                let e = old_result.err().or_else(|| new_result.err()).unwrap();
+                if json {
+                    return send_error(res, &e, json)
+                }
+                *res.status_mut() = status(&e);
+                return res.send(render::diff_form_page(old,
+                                                       new,
+                                                       &options,
+                                                       &old_errors,
+                                                       &new_errors))
+            }
+        };
+        let changes = diff::diff(sort::arrange(parsed.0, options.order),
+                                 sort::arrange(parsed.1, options.order));
+
+        let strings = options.language.strings();
+        let removed = Report {
+            title: String::from(strings.removed),
+            lines: if changes.removed.is_empty() {
+                vec![String::from(strings.nothing_removed)]
+            } else {
+                changes.removed.iter().map(|&(n, ref name)| format!("{} {}", n, name)).collect()
+            },
+        };
+
+        match render::results_page(&changes.added, &options, None, &[removed], None) {
+            Ok(doc) => return res.send(doc),
+            Err(e) => return send_error(res, &e, json),
+        }
+    }));
+
+    server.get("/proxygen/api/search",
+               middleware!(|req, mut res| {
+        let names = match search_filters(req.query())
+            .and_then(|search| card::search(&search, SEARCH_LIMIT)) {
+            Ok(v) => v,
+            Err(e) => return send_error(res, &e, true),
+        };
+        res.set(MediaType::Json);
+        return res.send(serde_json::to_string(&names).unwrap())
+    }));
+
+    server.get("/proxygen/api/complete",
+               middleware!(|req, mut res| {
+        let names = match card::complete(req.query().get("q").unwrap_or(""), COMPLETE_LIMIT) {
+            Ok(v) => v,
+            Err(e) => return send_error(res, &e, true),
+        };
+        res.set(MediaType::Json);
+        return res.send(serde_json::to_string(&names).unwrap())
+    }));
+
+    server.get("/proxygen/s/:id",
+               middleware!(|req, mut res| {
+        let json = wants_json(req);
+        let decklist = match permalink::decode(req.param("id").unwrap_or(""), max_body_size) {
+            Ok(v) => v,
+            Err(e) => return send_error(res, &e, json),
+        };
+
+        let query = req.query();
+        let options = render_options(query, default_theme);
+
+        match make_sheet(&decklist, None, &options, max_cards) {
+            Ok(doc) => return res.send(doc),
+            Err(e) => return send_error(res, &e, json),
+        }
+    }));
+
+    {
+        let store = store.clone();
+        server.get("/proxygen/decks",
+                   middleware!(|req, mut res| {
+            let json = wants_json(req);
+            let listings: Vec<DeckListing> = match store.list() {
+                Ok(v) => {
+                    v.into_iter()
+                        .map(|(slug, deck)| DeckListing { slug: slug, name: deck.name })
+                        .collect()
+                }
+                Err(e) => return send_error(res, &e, json),
+            };
+            res.set(MediaType::Json);
+            return res.send(serde_json::to_string(&listings).unwrap())
+        }));
+    }
+
+    {
+        let store = store.clone();
+        server.post("/proxygen/decks",
+                    middleware!(|req, mut res| {
+            let json = wants_json(req);
+            if let Err(e) = check_body_size(req, max_body_size) {
+                return send_error(res, &e, json)
+            }
+
+            let form_body = try_with!(res, req.form_body());
+            let (name, decklist) = match (form_body.get("name"), form_body.get("decklist")) {
+                (Some(name), Some(decklist)) => (name, decklist),
+                (None, _) => return send_error(res, &missing_field("name"), json),
+                (_, None) => return send_error(res, &missing_field("decklist"), json),
+            };
+            let options = render_options(form_body, default_theme);
+
+            match store.save(name, decklist) {
+                Ok(slug) => {
+                    return redirect(res,
+                                    format!("/proxygen?deck={}&{}", slug, options.query_string()))
+                }
+                Err(ref e) if !json => {
+                    let mut form = FormPage::new(decklist, &options);
+                    form.deck_name = String::from(name);
+                    form.saved_decks = saved_decks(&store);
+                    *res.status_mut() = status(e);
+                    return res.send(form_with_error(form, e))
+                }
+                Err(e) => return send_error(res, &e, json),
+            }
+        }));
+    }
+
+    {
+        let store = store.clone();
+        server.get("/proxygen/decks/:slug",
+                   middleware!(|req, mut res| {
+            let json = wants_json(req);
+            let deck = match store.load(req.param("slug").unwrap_or("")) {
+                Ok(v) => v,
+                Err(e) => return send_error(res, &e, json),
+            };
+
+            let query = req.query();
+            let options = render_options(query, default_theme);
+
+            match make_sheet(&deck.decklist, None, &options, max_cards) {
+                Ok(doc) => return res.send(doc),
+                Err(e) => return send_error(res, &e, json),
+            }
+        }));
+    }
+
+    {
+        let store = store.clone();
+        server.post("/proxygen/decks/:slug/rename",
+                    middleware!(|req, mut res| {
+            let json = wants_json(req);
+            if let Err(e) = check_body_size(req, max_body_size) {
+                return send_error(res, &e, json)
+            }
+
+            let slug = String::from(req.param("slug").unwrap_or(""));
+            let form_body = try_with!(res, req.form_body());
+            let name = match form_body.get("name") {
+                Some(v) => v,
+                None => return send_error(res, &missing_field("name"), json),
+            };
+
+            match store.rename(&slug, name) {
+                Ok(_) => return redirect(res, String::from("/proxygen")),
+                Err(e) => return send_error(res, &e, json),
+            }
+        }));
+    }
+
+    {
+        let store = store.clone();
+        server.post("/proxygen/decks/:slug/delete",
+                    middleware!(|req, mut res| {
+            let json = wants_json(req);
+            match store.delete(req.param("slug").unwrap_or("")) {
+                Ok(()) => return redirect(res, String::from("/proxygen")),
+                Err(e) => return send_error(res, &e, json),
+            }
+        }));
+    }
+
+    for asset in ASSETS.iter() {
+        server.get(asset.path(),
+                   middleware!(|req, mut res| {
+            res.set(ETag(EntityTag::strong(String::from(asset.version()))));
+            res.set(CacheControl(vec![CacheDirective::Public,
+                                      CacheDirective::MaxAge(ASSET_MAX_AGE)]));
+            if is_fresh(req, asset) {
+                *res.status_mut() = StatusCode::NotModified;
+                return res.send("")
+            }
+            res.set(ContentType(asset.content_type.parse().unwrap()));
+            return res.send(asset.body)
+        }));
+    }
+
+    server.listen((config.address.as_str(), config.port));
+}
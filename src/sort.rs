@@ -0,0 +1,108 @@
+use std::sync::Arc;
+
+use super::card::{sanitize_name, Card, TYPES, WUBRG};
+use super::decklist::Section;
+
+/// Orders the proxies can be printed in.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Order {
+    AsEntered,
+    Alphabetical,
+    ManaValue,
+    Color,
+    Type,
+    Section,
+}
+
+pub const ORDERS: [Order; 6] = [Order::AsEntered,
+                                Order::Alphabetical,
+                                Order::ManaValue,
+                                Order::Color,
+                                Order::Type,
+                                Order::Section];
+
+impl Default for Order {
+    fn default() -> Order {
+        Order::AsEntered
+    }
+}
+
+impl Order {
+    pub fn from_param(param: &str) -> Option<Order> {
+        ORDERS.iter().find(|order| order.param() == param).cloned()
+    }
+
+    /// Value used for the order in forms and query strings.
+    pub fn param(&self) -> &'static str {
+        match *self {
+            Order::AsEntered => "as_entered",
+            Order::Alphabetical => "name",
+            Order::ManaValue => "mana_value",
+            Order::Color => "color",
+            Order::Type => "type",
+            Order::Section => "section",
+        }
+    }
+}
+
+/// Key that sorts mono-colored cards in WUBRG order, then multicolored,
+/// then colorless cards, with lands last.
+fn color_group(card: &Card) -> usize {
+    if card.is_land() {
+        return WUBRG.len() + 2;
+    }
+    let colors = card.colors();
+    match colors.len() {
+        0 => WUBRG.len() + 1,
+        1 => WUBRG.iter().position(|color| *color == colors[0]).unwrap(),
+        _ => WUBRG.len(),
+    }
+}
+
+/// Position in `TYPES` of the first type the card has, so artifact
+/// creatures go with the creatures.
+fn type_group(card: &Card) -> usize {
+    let types = card.types();
+    TYPES.iter().position(|t| types.iter().any(|card_type| card_type == t)).unwrap_or(TYPES.len())
+}
+
+/// Sorts resolved cards in place. Cards that compare equal keep their
+/// order, and ties are broken by name. Sections aren't known here, so
+/// `Order::Section` leaves the cards as they are.
+pub fn sort(cards: &mut [(u64, Arc<Card>)], order: Order) {
+    match order {
+        Order::AsEntered | Order::Section => {}
+        Order::Alphabetical => cards.sort_by_key(|&(_, ref card)| sanitize_name(&card.name())),
+        Order::ManaValue => {
+            cards.sort_by_key(|&(_, ref card)| {
+                (card.is_land(), card.mana_value(), sanitize_name(&card.name()))
+            })
+        }
+        Order::Color => {
+            cards.sort_by_key(|&(_, ref card)| (color_group(card), sanitize_name(&card.name())))
+        }
+        Order::Type => {
+            cards.sort_by_key(|&(_, ref card)| (type_group(card), sanitize_name(&card.name())))
+        }
+    }
+}
+
+/// Puts the sections of a parsed decklist together in the given order.
+/// Grouping by section gathers sections with the same heading, so a deck
+/// with two "Sideboard" parts prints one sideboard.
+pub fn arrange(sections: Vec<Section>, order: Order) -> Vec<(u64, Arc<Card>)> {
+    let mut groups: Vec<Section> = Vec::new();
+    for section in sections {
+        let position = groups.iter()
+            .position(|group| group.name.to_lowercase() == section.name.to_lowercase());
+        match position {
+            Some(i) if order == Order::Section => groups[i].cards.extend(section.cards),
+            _ => groups.push(section),
+        }
+    }
+
+    let mut cards: Vec<(u64, Arc<Card>)> =
+        groups.into_iter().flat_map(|group| group.cards).collect();
+    sort(&mut cards, order);
+    cards
+}
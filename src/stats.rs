@@ -0,0 +1,137 @@
+use std::cmp;
+
+use super::card::{TYPES, WUBRG};
+use super::decklist::{Part, Section};
+use super::i18n::Strings;
+
+/// The mana curve's last bucket holds everything at this mana value or more.
+const CURVE_TOP: usize = 7;
+
+/// Numbers describing the main deck of a resolved decklist, for a cover
+/// sheet.
+#[derive(Debug)]
+pub struct DeckStats {
+    pub cards: u64,
+    pub lands: u64,
+    /// How many nonland cards have each mana value, from 0 up to
+    /// `CURVE_TOP` or more.
+    pub curve: [u64; CURVE_TOP + 1],
+    /// How many nonland cards are each color, in WUBRG order, then how many
+    /// are colorless.
+    pub colors: [u64; 6],
+    /// How many cards have each type, in the order of `TYPES`.
+    pub types: [u64; 7],
+    /// Average mana value of the nonland cards.
+    pub average_mana_value: f64,
+}
+
+impl DeckStats {
+    /// Sideboards and maybeboards aren't counted.
+    pub fn new(sections: &[Section]) -> DeckStats {
+        let mut stats = DeckStats {
+            cards: 0,
+            lands: 0,
+            curve: [0; CURVE_TOP + 1],
+            colors: [0; 6],
+            types: [0; 7],
+            average_mana_value: 0.0,
+        };
+        let mut total_mana_value = 0;
+
+        let main = sections.iter().filter(|section| section.part() == Part::Main);
+        for &(n, ref card) in main.flat_map(|section| section.cards.iter()) {
+            stats.cards += n;
+
+            let types = card.types();
+            for (i, t) in TYPES.iter().enumerate() {
+                if types.iter().any(|card_type| card_type == t) {
+                    stats.types[i] += n;
+                }
+            }
+
+            if card.is_land() {
+                stats.lands += n;
+                continue;
+            }
+
+            let mana_value = card.mana_value();
+            total_mana_value += mana_value * n;
+            stats.curve[cmp::min(mana_value as usize, CURVE_TOP)] += n;
+
+            let colors = card.colors();
+            if colors.is_empty() {
+                stats.colors[WUBRG.len()] += n;
+            }
+            for (i, color) in WUBRG.iter().enumerate() {
+                if colors.contains(color) {
+                    stats.colors[i] += n;
+                }
+            }
+        }
+
+        let nonland = stats.cards - stats.lands;
+        if nonland > 0 {
+            stats.average_mana_value = total_mana_value as f64 / nonland as f64;
+        }
+        stats
+    }
+
+    /// A page of the numbers, printed before the proxies.
+    pub fn to_html(&self, strings: &Strings) -> String {
+        let highest = *self.curve.iter().max().unwrap_or(&0);
+        let bar_width = |n: u64| {
+            if highest == 0 {
+                String::from("width: 0")
+            } else {
+                format!("width: {}mm", n * 100 / highest)
+            }
+        };
+
+        let mut s = String::new();
+        html!(s,
+            div class="cover_sheet" {
+                h1 { (strings.stats_heading) }
+                table class="stats_totals" {
+                    tr { th { (strings.total_cards) } td { (self.cards) } }
+                    tr { th { (strings.lands) } td { (self.lands) } }
+                    tr {
+                        th { (strings.average_mana_value) }
+                        td { (format!("{:.2}", self.average_mana_value)) }
+                    }
+                }
+                h2 { (strings.mana_curve) }
+                table class="mana_curve" {
+                    @for (mana_value, n) in self.curve.iter().enumerate() {
+                        tr {
+                            th {
+                                @if mana_value == CURVE_TOP {
+                                    (mana_value) "+"
+                                } @else {
+                                    (mana_value)
+                                }
+                            }
+                            td { div class="bar" style=(bar_width(*n)) {} }
+                            td { (n) }
+                        }
+                    }
+                }
+                h2 { (strings.colors) }
+                table class="stats_colors" {
+                    @for (name, n) in strings.color_names.iter().zip(self.colors.iter()) {
+                        tr { th { (name) } td { (n) } }
+                    }
+                }
+                h2 { (strings.card_types) }
+                table class="stats_types" {
+                    @for (name, n) in strings.type_names.iter().zip(self.types.iter()) {
+                        @if *n > 0 {
+                            tr { th { (name) } td { (n) } }
+                        }
+                    }
+                }
+            }
+        )
+            .unwrap();
+        s
+    }
+}
@@ -0,0 +1,153 @@
+use std::fs::{self, File};
+use std::io::{ErrorKind, Read, Write};
+use std::path::PathBuf;
+
+use super::serde_json;
+
+use super::error::ProxygenError;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SavedDeck {
+    pub name: String,
+    pub decklist: String,
+}
+
+/// What GET /proxygen/decks lists for each deck.
+#[derive(Serialize, Debug)]
+pub struct DeckListing {
+    pub slug: String,
+    pub name: String,
+}
+
+/// Named decklists, one JSON file per deck in a directory. Files are named
+/// after the deck's slug, which is also how the web routes refer to decks.
+#[derive(Debug, Clone)]
+pub struct DeckStore {
+    dir: PathBuf,
+}
+
+/// Turns a deck name into something safe for file names and URLs. Letters
+/// and digits outside ASCII become their code points, "u" and hex digits,
+/// so names in any script get a slug ("青単" is "u9752-u5358").
+pub fn slug(name: &str) -> Result<String, ProxygenError> {
+    let mut words = String::new();
+    for c in name.to_lowercase().chars() {
+        if c.is_alphanumeric() && c < '\u{80}' {
+            words.push(c);
+        } else if c.is_alphanumeric() {
+            words.push_str(&format!(" u{:x} ", c as u32));
+        } else {
+            words.push(' ');
+        }
+    }
+    let slug = words.split_whitespace().collect::<Vec<&str>>().join("-");
+
+    if slug.is_empty() {
+        Err(ProxygenError::InvalidDeckName(String::from(name)))
+    } else {
+        Ok(slug)
+    }
+}
+
+impl DeckStore {
+    pub fn open(dir: &str) -> Result<DeckStore, ProxygenError> {
+        try!(fs::create_dir_all(dir));
+        Ok(DeckStore { dir: PathBuf::from(dir) })
+    }
+
+    /// The file a deck is saved in. Slugs come from URLs, so only ones that
+    /// `slug` could have made are accepted, which keeps "../" and the like
+    /// out of the path.
+    fn path(&self, slug: &str) -> Result<PathBuf, ProxygenError> {
+        match self::slug(slug) {
+            Ok(ref v) if v == slug => Ok(self.dir.join(format!("{}.json", slug))),
+            _ => Err(ProxygenError::DeckNotFound(String::from(slug))),
+        }
+    }
+
+    fn exists(&self, slug: &str) -> bool {
+        self.path(slug).map(|path| path.is_file()).unwrap_or(false)
+    }
+
+    fn write(&self, slug: &str, deck: &SavedDeck) -> Result<(), ProxygenError> {
+        let path = try!(self.path(slug));
+        // Write to the side and rename over, so readers never see half a file.
+        let tmp_path = self.dir.join(format!("{}.json.tmp", slug));
+        {
+            let mut file = try!(File::create(&tmp_path));
+            try!(file.write_all(try!(serde_json::to_string(deck)).as_bytes()));
+        }
+        try!(fs::rename(&tmp_path, path));
+        Ok(())
+    }
+
+    /// All saved decks as (slug, deck), sorted by name.
+    pub fn list(&self) -> Result<Vec<(String, SavedDeck)>, ProxygenError> {
+        let mut decks = Vec::new();
+        for dir_entry in try!(fs::read_dir(&self.dir)) {
+            let path = try!(dir_entry).path();
+            if path.extension().map_or(true, |ext| ext != "json") {
+                continue;
+            }
+            // Files proxygen couldn't have saved are left alone.
+            let slug = match path.file_stem().and_then(|stem| stem.to_str()) {
+                Some(v) if self.path(v).is_ok() => v,
+                _ => continue,
+            };
+            decks.push((String::from(slug), try!(self.load(slug))));
+        }
+        decks.sort_by(|a, b| a.1.name.to_lowercase().cmp(&b.1.name.to_lowercase()));
+        Ok(decks)
+    }
+
+    pub fn load(&self, slug: &str) -> Result<SavedDeck, ProxygenError> {
+        let mut text = String::new();
+        let mut file = match File::open(try!(self.path(slug))) {
+            Ok(v) => v,
+            Err(ref e) if e.kind() == ErrorKind::NotFound => {
+                return Err(ProxygenError::DeckNotFound(String::from(slug)))
+            }
+            Err(e) => return Err(ProxygenError::from(e)),
+        };
+        try!(file.read_to_string(&mut text));
+        Ok(try!(serde_json::from_str(&text)))
+    }
+
+    /// Saves a deck, replacing any deck with the same slug. Returns the slug.
+    pub fn save(&self, name: &str, decklist: &str) -> Result<String, ProxygenError> {
+        let slug = try!(slug(name));
+        let deck = SavedDeck {
+            name: String::from(name.trim()),
+            decklist: String::from(decklist),
+        };
+        try!(self.write(&slug, &deck));
+        Ok(slug)
+    }
+
+    /// Renames a deck. Returns its new slug.
+    pub fn rename(&self, old_slug: &str, new_name: &str) -> Result<String, ProxygenError> {
+        let old_path = try!(self.path(old_slug));
+        let mut deck = try!(self.load(old_slug));
+        let new_slug = try!(slug(new_name));
+        if new_slug != old_slug && self.exists(&new_slug) {
+            return Err(ProxygenError::DeckAlreadyExists(String::from(new_name)));
+        }
+
+        deck.name = String::from(new_name.trim());
+        try!(self.write(&new_slug, &deck));
+        if new_slug != old_slug {
+            try!(fs::remove_file(old_path));
+        }
+        Ok(new_slug)
+    }
+
+    pub fn delete(&self, slug: &str) -> Result<(), ProxygenError> {
+        match fs::remove_file(try!(self.path(slug))) {
+            Ok(()) => Ok(()),
+            Err(ref e) if e.kind() == ErrorKind::NotFound => {
+                Err(ProxygenError::DeckNotFound(String::from(slug)))
+            }
+            Err(e) => Err(ProxygenError::from(e)),
+        }
+    }
+}
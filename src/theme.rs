@@ -0,0 +1,56 @@
+/// Built-in looks for the results page. Each theme is a stylesheet applied
+/// on top of results.css, so it only has to override what it changes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Theme {
+    Standard,
+    MinimalInk,
+    LargePrint,
+    Classic,
+}
+
+pub const THEMES: [Theme; 4] = [Theme::Standard,
+                                 Theme::MinimalInk,
+                                 Theme::LargePrint,
+                                 Theme::Classic];
+
+impl Default for Theme {
+    fn default() -> Theme {
+        Theme::Standard
+    }
+}
+
+impl Theme {
+    pub fn from_param(param: &str) -> Option<Theme> {
+        THEMES.iter().find(|theme| theme.param() == param).cloned()
+    }
+
+    /// Value used for the theme in forms and query strings.
+    pub fn param(&self) -> &'static str {
+        match *self {
+            Theme::Standard => "standard",
+            Theme::MinimalInk => "ink",
+            Theme::LargePrint => "large_print",
+            Theme::Classic => "classic",
+        }
+    }
+
+    /// Roughly how much larger than results.css this theme draws card text,
+    /// so the renderer knows when oracle text needs shrinking to fit.
+    pub fn text_scale(&self) -> f64 {
+        match *self {
+            Theme::Standard | Theme::MinimalInk => 1.0,
+            Theme::LargePrint => 1.4,
+            Theme::Classic => 1.1,
+        }
+    }
+
+    /// Name of the asset holding the theme's stylesheet, if it has one.
+    pub fn stylesheet(&self) -> Option<&'static str> {
+        match *self {
+            Theme::Standard => None,
+            Theme::MinimalInk => Some("themes/ink.css"),
+            Theme::LargePrint => Some("themes/large_print.css"),
+            Theme::Classic => Some("themes/classic.css"),
+        }
+    }
+}